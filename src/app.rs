@@ -1,22 +1,39 @@
 use std::{
-    collections::HashMap,
-    fs,
-    io::{self, ErrorKind, Read, Seek},
+    collections::{HashMap, HashSet},
+    fs, io,
+    ops::Range,
     path::{Component, Path, PathBuf},
     process::Command,
+    sync::mpsc::Sender,
     time::SystemTime,
 };
 
 use arboard::Clipboard;
-use ratatui::widgets::ListState;
+use ratatui::{text::Line, widgets::ListState};
+
+use crate::bookmarks::{self, Bookmarks};
+use crate::completion;
+use crate::config::{self, Config};
+use crate::events::Message;
+use crate::filter::{AttributeFilter, Filter, FilterMode, Query};
+use crate::finder::{Finder, FinderMatch};
+use crate::frecency::Frecency;
+use crate::grep::{ContentSearch, GrepMatch};
+use crate::history::History;
+use crate::journal::{Journal, Operation, Step};
+use crate::paste::{ConflictChoice, PastePlan};
+use crate::preview::{self, PreviewCache, PreviewKey, PreviewWorker};
+use crate::rename::{self, EditSession, RenamePattern, RenamePlan};
+use crate::sort::{Sort, SortKey};
+use crate::transfer::{CopyPolicy, JobOutcome, SymlinkPolicy, TransferEngine, TransferKind};
+use crate::trash::{self, TrashItem};
+use crate::watcher::{Changes, DirWatcher};
+use crate::worker::DirWorker;
 
 // =============================================================================
 // Constants
 // =============================================================================
 
-/// Maximum bytes to read for file preview
-const MAX_PREVIEW_BYTES: usize = 16 * 1024;
-
 /// Number of bytes to sample when detecting text vs binary content
 const TEXT_DETECTION_SAMPLE_SIZE: usize = 512;
 
@@ -44,7 +61,7 @@ where
 
 /// Validates a file or folder name for creation/renaming.
 /// Returns Ok(()) if valid, or Err with a user-friendly message if invalid.
-fn validate_filename(name: &str) -> Result<(), String> {
+pub fn validate_filename(name: &str) -> Result<(), String> {
     if name.is_empty() {
         return Err("Name cannot be empty".to_string());
     }
@@ -109,10 +126,46 @@ pub enum Mode {
     Search,
     /// Renaming a file or folder.
     Rename,
-    /// Awaiting delete confirmation.
+    /// Reviewing a bulk rename before applying it.
+    ConfirmBulkRename,
+    /// Typing a find/replace pattern for a bulk rename.
+    PatternRename,
+    /// Awaiting confirmation to move items to the trash.
     ConfirmDelete,
+    /// Awaiting confirmation to delete items permanently.
+    ConfirmPermanentDelete,
+    /// Deciding how to paste an item whose name is already taken.
+    Conflict,
+    /// Entering a new name for a conflicting pasted item.
+    ConflictRename,
+    /// Choosing how copies treat metadata and symlinks.
+    CopyOptions,
+    /// Awaiting confirmation to undo the last file operation.
+    ConfirmUndo,
+    /// Awaiting confirmation to redo the last undone file operation.
+    ConfirmRedo,
+    /// Picking a recently visited directory.
+    Recent,
+    /// Waiting for the key that changes the sort order.
+    Sort,
+    /// Waiting for the key to bookmark the current directory under.
+    BookmarkSet,
+    /// Waiting for the key of the bookmark to jump to.
+    BookmarkJump,
+    /// Managing saved bookmarks.
+    Bookmarks,
+    /// Browsing the trash.
+    Trash,
+    /// Awaiting confirmation to permanently remove an item from the trash.
+    ConfirmPurge,
+    /// Typing keywords to jump to a frequently visited directory.
+    Jump,
     /// Entering a path to navigate to.
     Path,
+    /// Fuzzy-finding files below the current directory.
+    Find,
+    /// Searching file contents below the current directory.
+    Grep,
     /// Creating a new file.
     NewFile,
     /// Creating a new folder.
@@ -124,12 +177,55 @@ pub enum Mode {
 /// Clipboard state for copy/cut operations.
 #[derive(Clone)]
 pub struct FileClipboard {
-    /// Path to the source file or directory.
-    pub path: PathBuf,
+    /// Paths to the source files or directories.
+    pub paths: Vec<PathBuf>,
     /// True if this is a cut (move) operation.
     pub is_cut: bool,
 }
 
+/// Browsing state of a tab in the background. The active tab's state lives
+/// in the `App` fields themselves.
+#[derive(Default)]
+pub struct Tab {
+    pub current_dir: PathBuf,
+    all_entries: Vec<Entry>,
+    filtered_indices: Vec<usize>,
+    state: ListState,
+    scroll: u16,
+    filter: String,
+    filter_positions: HashMap<usize, Vec<usize>>,
+    attribute_filters: Vec<AttributeFilter>,
+    selection: HashSet<PathBuf>,
+}
+
+impl Tab {
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.filtered_indices
+            .iter()
+            .filter_map(|&i| self.all_entries.get(i))
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    pub fn selection_len(&self) -> usize {
+        self.selection.len()
+    }
+
+    pub fn is_selected(&self, entry: &Entry) -> bool {
+        !self.selection.is_empty() && self.selection.contains(&self.current_dir.join(&entry.name))
+    }
+}
+
+/// Cached listing of the parent directory shown in the Miller-columns view.
+pub struct ParentColumn {
+    pub dir: PathBuf,
+    /// `(is_dir, name)` pairs in display order.
+    pub entries: Vec<(bool, String)>,
+    show_hidden: bool,
+}
+
 /// Main application state.
 pub struct App {
     pub current_dir: PathBuf,
@@ -142,15 +238,122 @@ pub struct App {
     pub mode: Mode,
     pub input: Vec<char>,
     pub cursor: usize,
+    /// Search filter applied to the listing
+    pub filter: String,
+    pub filter_mode: FilterMode,
+    /// Char indices of each filtered entry's name matched by the filter,
+    /// keyed by index into `all_entries`
+    pub filter_positions: HashMap<usize, Vec<usize>>,
+    /// Confirmed conditions like `size>10M`, kept across directories
+    pub attribute_filters: Vec<AttributeFilter>,
     pub show_hidden: bool,
     pub message: Option<String>,
     pub clipboard: Option<FileClipboard>,
+    /// Entries marked for batch operations (full paths in the current directory)
+    pub selection: HashSet<PathBuf>,
+    /// Open tabs; the entry at `active_tab` is a placeholder for the state above
+    tabs: Vec<Tab>,
+    pub active_tab: usize,
+    /// The second file list in dual-pane mode
+    other_pane: Option<Tab>,
+    /// True when the focused (active) panel is the right one
+    pub focus_right: bool,
+    /// Three-column layout: parent, current directory, preview
+    pub miller_columns: bool,
+    pub parent_column: Option<ParentColumn>,
+    /// Trash contents shown in trash mode
+    pub trash_items: Vec<TrashItem>,
+    pub trash_state: ListState,
+    /// Back/forward stacks and recently visited directories
+    pub history: History,
+    pub recent_state: ListState,
+    /// Saved directories, keyed by a single character
+    pub bookmarks: Bookmarks,
+    pub bookmark_state: ListState,
+    /// Visited directories ranked for the jump prompt
+    pub frecency: Frecency,
+    pub jump_matches: Vec<PathBuf>,
+    pub jump_state: ListState,
+    /// Child directories completing the path being typed
+    pub path_suggestions: Vec<String>,
+    pub path_state: ListState,
+    /// Recursive file finder and its current results
+    pub finder: Option<Finder>,
+    pub find_results: Vec<FinderMatch>,
+    pub find_state: ListState,
+    /// Last content search, kept so its results can be reopened
+    pub grep: Option<ContentSearch>,
+    pub grep_state: ListState,
+    /// Id of the last finder or content search started, so results of one
+    /// that was replaced are dropped
+    search_id: u64,
+    /// Line (within the previewed text) and byte range of a content match
+    pub preview_match: Option<(usize, Range<usize>)>,
+    /// File, line and byte range of a content match to preview once the
+    /// file is selected
+    grep_target: Option<(PathBuf, usize, Range<usize>)>,
+    /// Name of the entry last selected in each visited directory
+    cursor_memory: HashMap<PathBuf, String>,
+    /// Order of the file list in directories without a remembered one
+    pub sort: Sort,
+    /// Remember sort changes for the directory they were made in
+    pub sort_per_directory: bool,
+    sort_memory: HashMap<PathBuf, Sort>,
+    /// Undo/redo history of file operations
+    pub journal: Journal,
+    /// Transfer job of the undo or redo in progress, with its label
+    journal_job: Option<(u64, &'static str)>,
+    /// Where background workers post their results
+    events: Sender<Message>,
+    /// Background worker for copy/move jobs
+    pub transfers: TransferEngine,
+    /// Paste waiting for conflict decisions
+    pub pending_paste: Option<PastePlan>,
+    /// How pasted copies treat metadata and symlinks
+    pub copy_policy: CopyPolicy,
+    /// Bulk rename waiting for review
+    pub rename_plan: Option<RenamePlan>,
+    /// Live old/new names of a pattern rename, with the problem of each row
+    pub pattern_preview: Vec<(String, String)>,
+    pub pattern_problems: Vec<Option<String>>,
+    /// Names being edited in an external editor
+    edit_session: Option<EditSession>,
+    /// Set when the event loop should launch the editor
+    editor_pending: bool,
+    /// Set after a quit request was refused because transfers are running
+    quit_requested: bool,
     git_statuses: HashMap<String, GitStatus>,
-    /// Cached directory for git status (avoids re-running git on same dir)
-    git_cache_dir: Option<PathBuf>,
+    /// Directory `git_statuses` belong to (avoids re-running git on same dir)
+    git_status_dir: Option<PathBuf>,
+    /// False once file operations may have changed the statuses
+    git_status_fresh: bool,
+    /// Runs `git status` once workers are connected; without it git runs
+    /// inline
+    git_worker: Option<DirWorker>,
+    /// Generation of the newest `git status` requested for each directory,
+    /// so an older result that finishes late is dropped
+    git_requests: HashMap<PathBuf, u64>,
+    /// Reads directory listings once workers are connected
+    listing_worker: Option<DirWorker>,
+    /// Generation of the newest listing requested for each directory
+    listing_requests: HashMap<PathBuf, u64>,
+    /// Entries to put the cursor on once the listing of a directory just
+    /// entered arrives, in order of preference
+    listing_select: Option<(PathBuf, Vec<String>)>,
+    /// Reports changes other programs make to the listing or preview
+    watcher: Option<DirWatcher>,
+    /// Changes reported while a prompt was open, applied once back in the
+    /// file list
+    deferred_changes: Option<(PathBuf, Changes)>,
+    preview_cache: PreviewCache,
+    /// Loads previews in the background once workers are connected
+    preview_worker: Option<PreviewWorker>,
+    /// Preview the worker is loading for the selection
+    preview_pending: Option<PreviewKey>,
 }
 
 /// Represents a file or directory entry.
+#[derive(Clone)]
 pub struct Entry {
     pub name: String,
     /// Pre-computed lowercase name for efficient filtering
@@ -174,12 +377,17 @@ pub enum GitStatus {
 }
 
 /// Preview content for the selected file.
+#[derive(Clone)]
 pub enum Preview {
     None,
+    /// Shown while a worker reads the selected path
+    Loading,
     Directory(Vec<String>),
     Text {
         content: String,
         extension: String,
+        /// `content` with syntax highlighting applied
+        lines: Vec<Line<'static>>,
     },
     Image {
         width: u32,
@@ -195,7 +403,32 @@ pub enum Preview {
 // =============================================================================
 
 impl App {
-    pub fn new(path: PathBuf) -> io::Result<Self> {
+    /// Opens `path`. Background workers post their results to `events`.
+    pub fn new(path: PathBuf, events: Sender<Message>) -> io::Result<Self> {
+        App::with_dirs(
+            path,
+            events,
+            config::config_dir().as_deref(),
+            config::data_dir().as_deref(),
+        )
+    }
+
+    /// Like `new`, but reads settings and bookmarks from `config_dir` and
+    /// the jump database from `data_dir`. Without them, the defaults are
+    /// used and nothing is saved.
+    pub fn with_dirs(
+        path: PathBuf,
+        events: Sender<Message>,
+        config_dir: Option<&Path>,
+        data_dir: Option<&Path>,
+    ) -> io::Result<Self> {
+        let (config, config_error) = Config::load(config_dir);
+        let (bookmarks, bookmarks_error) = Bookmarks::load(config_dir);
+        let (frecency, frecency_error) = Frecency::load(data_dir);
+        let (watcher, watcher_error) = match DirWatcher::new(events.clone()) {
+            Ok(watcher) => (Some(watcher), None),
+            Err(e) => (None, Some(format!("Cannot watch for changes: {}", e))),
+        };
         let mut app = App {
             current_dir: path.clone(),
             start_dir: path,
@@ -207,17 +440,78 @@ impl App {
             mode: Mode::Normal,
             input: Vec::with_capacity(64),
             cursor: 0,
+            filter: String::new(),
+            filter_mode: config.filter_mode,
+            filter_positions: HashMap::new(),
+            attribute_filters: Vec::new(),
             show_hidden: false,
             message: None,
             clipboard: None,
+            selection: HashSet::new(),
+            tabs: vec![Tab::default()],
+            active_tab: 0,
+            other_pane: None,
+            focus_right: false,
+            miller_columns: false,
+            parent_column: None,
+            trash_items: Vec::new(),
+            trash_state: ListState::default(),
+            history: History::default(),
+            recent_state: ListState::default(),
+            cursor_memory: HashMap::new(),
+            sort: config.sort,
+            sort_per_directory: config.sort_per_directory,
+            sort_memory: HashMap::new(),
+            bookmarks,
+            bookmark_state: ListState::default(),
+            frecency,
+            jump_matches: Vec::new(),
+            jump_state: ListState::default(),
+            path_suggestions: Vec::new(),
+            path_state: ListState::default(),
+            finder: None,
+            find_results: Vec::new(),
+            find_state: ListState::default(),
+            grep: None,
+            grep_state: ListState::default(),
+            search_id: 0,
+            preview_match: None,
+            grep_target: None,
+            journal: Journal::default(),
+            journal_job: None,
+            transfers: TransferEngine::new(events.clone()),
+            pending_paste: None,
+            copy_policy: config.copy_policy,
+            rename_plan: None,
+            pattern_preview: Vec::new(),
+            pattern_problems: Vec::new(),
+            edit_session: None,
+            editor_pending: false,
+            quit_requested: false,
             git_statuses: HashMap::with_capacity(64),
-            git_cache_dir: None,
+            git_status_dir: None,
+            git_status_fresh: false,
+            git_worker: None,
+            git_requests: HashMap::new(),
+            listing_worker: None,
+            listing_requests: HashMap::new(),
+            listing_select: None,
+            events,
+            watcher,
+            deferred_changes: None,
+            preview_cache: PreviewCache::default(),
+            preview_worker: None,
+            preview_pending: None,
         };
         app.refresh()?;
         if !app.filtered_indices.is_empty() {
             app.state.select(Some(0));
             app.update_preview();
         }
+        app.message = config_error
+            .or(bookmarks_error)
+            .or(frecency_error)
+            .or(watcher_error);
         Ok(app)
     }
 
@@ -229,17 +523,229 @@ impl App {
 
     /// Invalidate git cache to force re-fetching on next refresh
     fn invalidate_git_cache(&mut self) {
-        self.git_cache_dir = None;
+        self.git_status_fresh = false;
+    }
+
+    /// Runs slow work such as directory reads and `git status` on
+    /// background threads from now on.
+    pub fn start_workers(&mut self) {
+        let sender = self.events.clone();
+        self.preview_worker = Some(PreviewWorker::start(sender.clone()));
+        self.listing_worker = Some(DirWorker::start(sender.clone(), |dir, generation| {
+            let entries = read_entries(&dir);
+            Message::Listing {
+                dir,
+                generation,
+                entries,
+            }
+        }));
+        self.git_worker = Some(DirWorker::start(sender, |dir, generation| {
+            let statuses = get_git_status(&dir);
+            Message::GitStatus {
+                dir,
+                generation,
+                statuses,
+            }
+        }));
+    }
+
+    /// Applies a result a background worker posted. Input is handled by the
+    /// key bindings instead.
+    pub fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::Input(_) => {}
+            Message::Listing {
+                dir,
+                generation,
+                entries,
+            } => self.apply_listing(dir, generation, entries),
+            Message::GitStatus {
+                dir,
+                generation,
+                statuses,
+            } => self.apply_git_status(dir, generation, statuses),
+            Message::Preview { key, preview } => self.apply_preview(key, preview),
+            Message::FinderPaths {
+                finder,
+                paths,
+                done,
+            } => self.apply_finder_paths(finder, paths, done),
+            Message::GrepMatches {
+                search,
+                matches,
+                done,
+            } => self.apply_grep_matches(search, matches, done),
+            Message::TransferFinished(outcome) => self.apply_transfer(outcome),
+            Message::FilesChanged { dir, changes } => self.apply_file_changes(dir, changes),
+        }
     }
 
+    fn request_git_status(&mut self, dir: PathBuf) {
+        match &self.git_worker {
+            Some(worker) => {
+                let generation = worker.request(dir.clone());
+                self.git_requests.insert(dir, generation);
+            }
+            None => {
+                let statuses = get_git_status(&dir);
+                self.show_git_status(&dir, statuses);
+            }
+        }
+    }
+
+    /// Applies `git status` output a worker collected for `dir`, unless a
+    /// newer request for it is still running.
+    pub fn apply_git_status(
+        &mut self,
+        dir: PathBuf,
+        generation: u64,
+        statuses: HashMap<String, GitStatus>,
+    ) {
+        if self.git_requests.get(&dir) != Some(&generation) {
+            return;
+        }
+        self.git_requests.remove(&dir);
+        self.show_git_status(&dir, statuses);
+    }
+
+    fn show_git_status(&mut self, dir: &Path, statuses: HashMap<String, GitStatus>) {
+        if dir != self.current_dir {
+            if let Some(pane) = self.other_pane.as_mut().filter(|p| p.current_dir == dir) {
+                for entry in &mut pane.all_entries {
+                    entry.git_status = statuses.get(&entry.name).copied();
+                }
+            }
+            return;
+        }
+        self.git_statuses = statuses;
+        for entry in &mut self.all_entries {
+            entry.git_status = self.git_statuses.get(&entry.name).copied();
+        }
+        if self.sort_for(dir).key == SortKey::Git {
+            self.resort();
+        }
+    }
+
+    /// Re-reads the current directory. Once workers are connected the
+    /// listing arrives later through `apply_listing`; the old one stays up
+    /// until then.
     pub fn refresh(&mut self) -> io::Result<()> {
-        self.all_entries.clear();
+        // Only refresh git status if the directory or its files changed.
+        // With workers, entries show the previous statuses until it is done
+        if self.git_status_dir.as_ref() != Some(&self.current_dir) {
+            // Statuses of another directory don't apply here
+            self.git_statuses.clear();
+            self.git_status_dir = Some(self.current_dir.clone());
+            self.git_status_fresh = true;
+            self.request_git_status(self.current_dir.clone());
+        } else if !self.git_status_fresh {
+            self.git_status_fresh = true;
+            self.request_git_status(self.current_dir.clone());
+        }
+
+        self.request_listing(self.current_dir.clone())?;
+        self.update_parent_column();
+        Ok(())
+    }
+
+    fn request_listing(&mut self, dir: PathBuf) -> io::Result<()> {
+        match &self.listing_worker {
+            Some(worker) => {
+                let generation = worker.request(dir.clone());
+                self.listing_requests.insert(dir, generation);
+            }
+            None => {
+                let entries = read_entries(&dir)?;
+                self.show_listing(&dir, &entries);
+            }
+        }
+        Ok(())
+    }
+
+    /// Shows a listing a worker read for `dir`, unless a newer read of it
+    /// is still running.
+    pub fn apply_listing(
+        &mut self,
+        dir: PathBuf,
+        generation: u64,
+        entries: io::Result<Vec<Entry>>,
+    ) {
+        if self.listing_requests.get(&dir) != Some(&generation) {
+            return;
+        }
+        self.listing_requests.remove(&dir);
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                if dir == self.current_dir {
+                    self.message = Some(format!("Cannot read directory: {}", e));
+                }
+                return;
+            }
+        };
+
+        let previous = self.selected_preview_key();
+        self.show_listing(&dir, &entries);
+        let current = self.selected_preview_key();
+        if current != previous {
+            let same_path = previous.map(|k| k.path) == current.map(|k| k.path);
+            let scroll = self.scroll;
+            self.update_preview();
+            if same_path {
+                self.scroll = scroll;
+            }
+        }
+    }
+
+    /// Puts a fresh listing of `dir` wherever it is on screen: the active
+    /// pane, the other pane and the parent column.
+    fn show_listing(&mut self, dir: &Path, entries: &[Entry]) {
+        if let Some(column) = self.parent_column.as_mut().filter(|c| c.dir == dir) {
+            column.entries = parent_column_entries(entries, column.show_hidden);
+        }
+        if dir == self.current_dir {
+            let wanted = self
+                .listing_select
+                .take_if(|(dir, _)| *dir == self.current_dir)
+                .map(|(_, names)| names)
+                .unwrap_or_default();
+            self.install_entries(entries);
+            wanted.iter().any(|name| self.select_entry_named(name));
+        }
+        if let Some(pane) = self.other_pane.take_if(|p| p.current_dir == dir) {
+            let message = self.message.take();
+            let active = self.store_tab();
+            self.set_tab(pane);
+            self.install_entries(entries);
+            self.selection.retain(|path| path.exists());
+            self.other_pane = Some(self.store_tab());
+            self.set_tab(active);
+            self.message = message;
+        }
+    }
 
-        // Only refresh git status if directory changed
-        if self.git_cache_dir.as_ref() != Some(&self.current_dir) {
-            self.git_statuses = get_git_status(&self.current_dir);
-            self.git_cache_dir = Some(self.current_dir.clone());
+    /// Replaces the listing in the `App` fields with `entries`, keeping the
+    /// cursor on the same entry.
+    fn install_entries(&mut self, entries: &[Entry]) {
+        let selected = self.selected_entry().map(|e| e.name.clone());
+
+        // A pane in another directory than the git statuses keeps its own
+        let previous: HashMap<String, GitStatus> = self
+            .all_entries
+            .drain(..)
+            .filter_map(|e| Some((e.name, e.git_status?)))
+            .collect();
+        let statuses = if self.git_status_dir.as_ref() == Some(&self.current_dir) {
+            &self.git_statuses
+        } else {
+            &previous
+        };
+        let mut entries = entries.to_vec();
+        for entry in &mut entries {
+            entry.git_status = statuses.get(&entry.name).copied();
         }
+        let sort = self.sort_for(&self.current_dir);
+        entries.sort_by(|a, b| sort.compare(a, b));
 
         if self.current_dir.parent().is_some() {
             self.all_entries.push(Entry {
@@ -253,68 +759,60 @@ impl App {
                 git_status: None,
             });
         }
-
-        let mut entries: Vec<Entry> = fs::read_dir(&self.current_dir)?
-            .filter_map(|e| e.ok())
-            .map(|e| {
-                let metadata = e.metadata().ok();
-                let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-                let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                let modified = metadata.as_ref().and_then(|m| m.modified().ok());
-                let readonly = metadata
-                    .as_ref()
-                    .map(|m| m.permissions().readonly())
-                    .unwrap_or(false);
-                let name = e.file_name().to_string_lossy().to_string();
-                let is_hidden = is_hidden_file(&name, &e.path());
-                let git_status = self.git_statuses.get(&name).copied();
-                let name_lower = name.to_lowercase();
-                Entry {
-                    name,
-                    name_lower,
-                    is_dir,
-                    size,
-                    modified,
-                    is_hidden,
-                    readonly,
-                    git_status,
-                }
-            })
-            .collect();
-
-        // Sort: directories first, then alphabetically by lowercase name
-        entries.sort_by(|a, b| {
-            compare_entries_by_dir_and_name(a, b, |e| e.is_dir, |e| &e.name_lower)
-        });
-
         self.all_entries.extend(entries);
         self.apply_filter();
-        Ok(())
+        if let Some(name) = selected {
+            self.select_entry_named(&name);
+        }
     }
 
     pub fn apply_filter(&mut self) {
-        let query: String = self.input.iter().collect::<String>().to_lowercase();
-        self.filtered_indices = self
-            .all_entries
-            .iter()
-            .enumerate()
-            .filter(|(_, e)| {
-                // Always show ".." entry
-                if e.name == ".." {
-                    return true;
-                }
-                // Filter hidden files
-                if !self.show_hidden && e.is_hidden {
-                    return false;
-                }
-                // Apply search filter using pre-computed lowercase
-                if self.mode == Mode::Search && !query.is_empty() {
-                    return e.name_lower.contains(&query);
-                }
-                true
-            })
-            .map(|(i, _)| i)
-            .collect();
+        // Conditions typed into the search are applied before they are confirmed
+        let query = Query::parse(&self.filter);
+        if let Some(e) = &query.error {
+            self.message = Some(e.clone());
+        }
+        let filter = match Filter::new(self.filter_mode, &query.name) {
+            Ok(filter) => Some(filter).filter(|_| !query.name.is_empty()),
+            Err(e) => {
+                // Show everything until the pattern is fixed
+                self.message = Some(e);
+                None
+            }
+        };
+        let attributes: Vec<&AttributeFilter> =
+            self.attribute_filters.iter().chain(&query.attributes).collect();
+        let now = SystemTime::now();
+        let mut scored: Vec<(usize, i64)> = Vec::with_capacity(self.all_entries.len());
+        self.filter_positions.clear();
+        for (i, e) in self.all_entries.iter().enumerate() {
+            // Always show ".." entry, first
+            if e.name == ".." {
+                scored.push((i, i64::MAX));
+                continue;
+            }
+            // Filter hidden files
+            if !self.show_hidden && e.is_hidden {
+                continue;
+            }
+            if !attributes.iter().all(|a| a.matches(e, now)) {
+                continue;
+            }
+            let Some(filter) = &filter else {
+                scored.push((i, 0));
+                continue;
+            };
+            if let Some((score, positions)) = filter.matches(&e.name, &e.name_lower) {
+                scored.push((i, score));
+                self.filter_positions.insert(i, positions);
+            }
+        }
+        // Best fuzzy matches first; the sort is stable so ties keep the
+        // directory order
+        if self.filter_mode == FilterMode::Fuzzy && filter.is_some() {
+            scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        }
+        self.filtered_indices = scored.into_iter().map(|(i, _)| i).collect();
 
         // Reset selection if out of bounds
         if let Some(selected) = self.state.selected() {
@@ -347,158 +845,201 @@ impl App {
         })
     }
 
+    /// Shows the preview of the selected entry: from the cache if it is
+    /// unchanged, otherwise loaded by a worker (or inline without one).
     pub fn update_preview(&mut self) {
         self.scroll = 0;
-        self.preview = match self.selected_entry() {
-            None => Preview::None,
-            Some(entry) if entry.is_dir => {
-                let path = if entry.name == ".." {
-                    self.current_dir.parent().map(|p| p.to_path_buf())
-                } else {
-                    Some(self.current_dir.join(&entry.name))
-                };
-                path.map(|p| self.load_directory_preview(&p))
-                    .unwrap_or(Preview::None)
+        self.preview_match = None;
+        let Some(mut key) = self.selected_preview_key() else {
+            self.cancel_preview_load();
+            self.preview = Preview::None;
+            return;
+        };
+        // A content match waits for its file; any other selection drops it
+        if let Some((path, line, range)) = self.grep_target.take() {
+            if path == key.path && !key.is_dir {
+                key.line = Some(line);
+                let start = preview::match_window_start(line);
+                self.preview_match = Some((line.saturating_sub(1) - start, range));
+            }
+        }
+        if self.preview_pending.as_ref() == Some(&key) {
+            return;
+        }
+        if let Some(preview) = self.preview_cache.get(&key) {
+            self.cancel_preview_load();
+            self.preview = preview;
+            return;
+        }
+        match &self.preview_worker {
+            Some(worker) => {
+                // Replaces the load for the previous selection
+                worker.request(key.clone());
+                self.preview_pending = Some(key);
+                self.preview = Preview::Loading;
             }
-            Some(entry) => {
-                let path = self.current_dir.join(&entry.name);
-                self.load_file_preview(&path)
+            None => {
+                let preview = preview::load(&key);
+                self.preview_cache.insert(key, preview.clone());
+                self.preview = preview;
             }
-        };
+        }
+    }
+
+    fn cancel_preview_load(&mut self) {
+        if self.preview_pending.take().is_some() {
+            if let Some(worker) = &self.preview_worker {
+                worker.cancel();
+            }
+        }
+    }
+
+    fn selected_preview_key(&self) -> Option<PreviewKey> {
+        let entry = self.selected_entry()?;
+        Some(PreviewKey {
+            path: self.selected_path()?,
+            is_dir: entry.is_dir,
+            // ".." carries no metadata of the parent, so it is not cached
+            modified: entry.modified.filter(|_| entry.name != ".."),
+            size: entry.size,
+            show_hidden: self.show_hidden,
+            line: None,
+        })
+    }
+
+    /// Shows a preview a worker loaded, unless the selection moved on.
+    pub fn apply_preview(&mut self, key: PreviewKey, preview: Preview) {
+        if self.preview_pending.as_ref() != Some(&key) {
+            return;
+        }
+        self.preview_pending = None;
+        self.preview_cache.insert(key, preview.clone());
+        self.preview = preview;
     }
 
+    /// Changes to `target` and records the move in the history.
     fn navigate_to(&mut self, target: PathBuf) -> io::Result<()> {
         let previous_dir = self.current_dir.clone();
+        self.change_dir(target)?;
+        self.history.visit(&previous_dir, &self.current_dir);
+        if let Err(e) = self.frecency.add(&self.current_dir) {
+            self.message = Some(format!("Cannot save jump database: {}", e));
+        }
+        Ok(())
+    }
+
+    /// Like `navigate_to`, and puts the cursor on `name` once the listing
+    /// of `dir` is shown.
+    fn navigate_to_entry(&mut self, dir: &Path, name: String) -> io::Result<()> {
+        let canonical = dir.canonicalize()?;
+        self.listing_select = Some((canonical.clone(), vec![name]));
+        let result = self.navigate_to(canonical);
+        if result.is_err() {
+            self.listing_select = None;
+        }
+        result
+    }
+
+    fn change_dir(&mut self, target: PathBuf) -> io::Result<()> {
+        let previous_dir = self.current_dir.clone();
+        let previous_entry = self
+            .selected_entry()
+            .map(|e| e.name.clone())
+            .filter(|name| name != "..");
         let canonical = target.canonicalize()?;
-        self.current_dir = canonical;
+        // Fail before leaving, even when a worker reads the listing
+        fs::read_dir(&canonical)?;
 
-        if let Err(err) = self.refresh() {
-            self.current_dir = previous_dir;
-            return Err(err);
+        if let Some(name) = previous_entry {
+            self.cursor_memory.insert(previous_dir.clone(), name);
         }
 
         self.input.clear();
         self.cursor = 0;
+        self.filter.clear();
         self.mode = Mode::Normal;
+        self.selection.clear();
+
+        // An entry the caller asked for comes first. Going up selects the
+        // directory just left; otherwise restore the entry that was selected
+        // on the last visit
+        let requested = self
+            .listing_select
+            .take()
+            .filter(|(dir, _)| *dir == canonical)
+            .map(|(_, names)| names)
+            .unwrap_or_default();
+        let child = previous_dir
+            .parent()
+            .filter(|parent| *parent == canonical)
+            .and_then(|_| previous_dir.file_name())
+            .map(|name| name.to_string_lossy().to_string());
+        let remembered = self.cursor_memory.get(&canonical).cloned();
+        let wanted = requested.into_iter().chain(child).chain(remembered).collect();
+        self.listing_select = Some((canonical.clone(), wanted));
+
+        self.current_dir = canonical;
+        self.all_entries.clear();
+        self.filtered_indices.clear();
         self.state.select(Some(0));
+        if let Err(err) = self.refresh() {
+            self.current_dir = previous_dir;
+            return Err(err);
+        }
         self.update_preview();
         Ok(())
     }
 
-    fn load_directory_preview(&self, path: &Path) -> Preview {
-        match fs::read_dir(path) {
-            Ok(entries) => {
-                // (is_dir, name, name_lower) - pre-compute lowercase for sorting
-                let mut items: Vec<(bool, String, String)> = entries
-                    .filter_map(|e| e.ok())
-                    .filter_map(|e| {
-                        let name = e.file_name().to_string_lossy().to_string();
-                        let hidden = is_hidden_file(&name, &e.path());
-                        if !self.show_hidden && hidden {
-                            return None;
-                        }
-                        let is_dir = e.metadata().map(|m| m.is_dir()).unwrap_or(false);
-                        let name_lower = name.to_lowercase();
-                        Some((is_dir, name, name_lower))
-                    })
-                    .collect();
-
-                // Sort: directories first, then alphabetically by lowercase name
-                items.sort_by(|a, b| {
-                    compare_entries_by_dir_and_name(a, b, |e| e.0, |e| &e.2)
-                });
-
-                let formatted: Vec<String> = items
-                    .into_iter()
-                    .map(|(is_dir, name, _)| {
-                        if is_dir {
-                            format!("📁 {}", name)
-                        } else {
-                            format!("📄 {}", name)
-                        }
-                    })
-                    .collect();
-
-                Preview::Directory(formatted)
+    /// Moves the cursor to the visible entry called `name`.
+    fn select_entry_named(&mut self, name: &str) -> bool {
+        let position = self.entries().position(|e| e.name == name);
+        match position {
+            Some(index) => {
+                self.state.select(Some(index));
+                true
             }
-            Err(e) => Preview::Error(format!("Cannot read directory: {}", e)),
+            None => false,
         }
     }
 
-    fn load_file_preview(&self, path: &Path) -> Preview {
-        let extension = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-
-        // Check for image files
-        if matches!(
-            extension.as_str(),
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "webp"
-        ) {
-            return self.load_image_preview(path, &extension);
-        }
-
-        const MAX_PREVIEW: usize = MAX_PREVIEW_BYTES;
-
-        let mut file = match fs::File::open(path) {
-            Ok(f) => f,
-            Err(e) => return Preview::Error(format!("Cannot open: {}", e)),
-        };
-
-        let mut buffer = vec![0u8; MAX_PREVIEW];
-        let bytes_read = match file.read(&mut buffer) {
-            Ok(n) => n,
-            Err(e) => return Preview::Error(format!("Cannot read: {}", e)),
-        };
-        buffer.truncate(bytes_read);
+    // =========================================================================
+    // Miller Columns
+    // =========================================================================
 
-        if is_text(&buffer) {
-            match String::from_utf8(buffer) {
-                Ok(s) => Preview::Text {
-                    content: s,
-                    extension,
-                },
-                Err(e) => Preview::Binary(e.into_bytes()),
-            }
-        } else {
-            Preview::Binary(buffer)
-        }
+    pub fn toggle_miller_columns(&mut self) {
+        self.miller_columns = !self.miller_columns;
+        self.parent_column = None;
+        self.update_parent_column();
+        self.message = Some(format!(
+            "Miller columns: {}",
+            if self.miller_columns { "on" } else { "off" }
+        ));
     }
 
-    fn load_image_preview(&self, path: &Path, ext: &str) -> Preview {
-        let mut file = match fs::File::open(path) {
-            Ok(f) => f,
-            Err(e) => return Preview::Error(format!("Cannot open: {}", e)),
-        };
-
-        let mut header = [0u8; 32];
-        if file.read(&mut header).is_err() {
-            return Preview::Error("Cannot read image header".to_string());
-        }
-
-        let (width, height, format): (u32, u32, &'static str) = match ext {
-            "png" => parse_png_dimensions(&header),
-            "jpg" | "jpeg" => {
-                // JPEG requires reading more data
-                let mut full_header = vec![0u8; 512];
-                let _ = file.rewind();
-                let _ = file.read(&mut full_header);
-                parse_jpeg_dimensions(&full_header)
-            }
-            "gif" => parse_gif_dimensions(&header),
-            "bmp" => parse_bmp_dimensions(&header),
-            "ico" => (0, 0, "ICO"),
-            "webp" => (0, 0, "WEBP"),
-            _ => (0, 0, "Image"),
+    /// Re-reads the parent column, unless the cached listing is still for
+    /// the same parent and hidden-file setting.
+    fn update_parent_column(&mut self) {
+        if !self.miller_columns {
+            return;
+        }
+        let Some(parent) = self.current_dir.parent().map(Path::to_path_buf) else {
+            self.parent_column = None;
+            return;
         };
-
-        Preview::Image {
-            width,
-            height,
-            format,
+        let cached = self
+            .parent_column
+            .as_ref()
+            .is_some_and(|c| c.dir == parent && c.show_hidden == self.show_hidden);
+        if cached {
+            return;
         }
+        // Filled in once the listing is read
+        self.parent_column = Some(ParentColumn {
+            dir: parent.clone(),
+            entries: Vec::new(),
+            show_hidden: self.show_hidden,
+        });
+        let _ = self.request_listing(parent);
     }
 
     pub fn enter_selected(&mut self) -> io::Result<()> {
@@ -562,476 +1103,2018 @@ impl App {
     }
 
     // =========================================================================
-    // Search/Filter
+    // Directory History
     // =========================================================================
 
-    pub fn start_search(&mut self) {
-        self.mode = Mode::Search;
-        self.input.clear();
-        self.message = Some("Search: type to filter".to_string());
+    pub fn go_back(&mut self) {
+        let current = self.current_dir.clone();
+        match self.history.back(&current) {
+            Some(target) => self.step_history(target, true),
+            None => self.message = Some("No previous directory".to_string()),
+        }
     }
 
-    pub fn cancel_search(&mut self) {
-        self.mode = Mode::Normal;
-        self.input.clear();
-        self.message = None;
-        self.apply_filter();
-        self.update_preview();
+    pub fn go_forward(&mut self) {
+        let current = self.current_dir.clone();
+        match self.history.forward(&current) {
+            Some(target) => self.step_history(target, false),
+            None => self.message = Some("No next directory".to_string()),
+        }
     }
 
-    pub fn confirm_search(&mut self) {
-        self.mode = Mode::Normal;
+    fn step_history(&mut self, target: PathBuf, went_back: bool) {
+        if let Err(err) = self.change_dir(target.clone()) {
+            self.history.discard(&target, went_back);
+            self.message = Some(format!(
+                "Cannot open {}: {} (removed from history)",
+                target.display(),
+                err
+            ));
+        }
+    }
+
+    pub fn open_recent(&mut self) {
+        if self.history.recent().is_empty() {
+            self.message = Some("No recent directories yet".to_string());
+            return;
+        }
+        self.mode = Mode::Recent;
+        self.recent_state.select(Some(0));
         self.message = None;
-        // Keep the filter applied
     }
 
-    pub fn update_search(&mut self, c: char) {
-        self.input.push(c);
-        self.apply_filter();
-        self.update_preview();
+    pub fn close_recent(&mut self) {
+        self.mode = Mode::Normal;
     }
 
-    pub fn backspace_search(&mut self) {
-        self.input.pop();
-        self.apply_filter();
-        self.update_preview();
+    pub fn recent_move_up(&mut self) {
+        if let Some(selected) = self.recent_state.selected() {
+            self.recent_state.select(Some(selected.saturating_sub(1)));
+        }
     }
 
-    // =========================================================================
-    // Hidden Files
-    // =========================================================================
+    pub fn recent_move_down(&mut self) {
+        if let Some(selected) = self.recent_state.selected() {
+            if selected + 1 < self.history.recent().len() {
+                self.recent_state.select(Some(selected + 1));
+            }
+        }
+    }
 
-    pub fn toggle_hidden(&mut self) {
-        self.show_hidden = !self.show_hidden;
-        self.message = Some(format!(
-            "Hidden files: {}",
-            if self.show_hidden { "shown" } else { "hidden" }
-        ));
-        self.apply_filter();
-        self.update_preview();
+    /// Jumps to the directory highlighted in the recent popup.
+    pub fn jump_to_recent(&mut self) {
+        let target = self
+            .recent_state
+            .selected()
+            .and_then(|i| self.history.recent().get(i))
+            .cloned();
+        self.mode = Mode::Normal;
+        if let Some(target) = target {
+            self.jump_to(target);
+        }
     }
 
     // =========================================================================
-    // File Operations
+    // Bookmarks
     // =========================================================================
 
-    pub fn yank_path(&mut self) {
-        if let Some(path) = self.selected_path() {
-            let path_str = path.to_string_lossy().to_string();
-            match Clipboard::new().and_then(|mut cb| cb.set_text(&path_str)) {
-                Ok(_) => self.message = Some(format!("Copied: {}", path_str)),
-                Err(e) => self.message = Some(format!("Failed to copy: {}", e)),
-            }
-        }
+    pub fn start_set_bookmark(&mut self) {
+        self.mode = Mode::BookmarkSet;
+        self.message = Some("Bookmark this directory as (a-z, A-Z, 0-9):".to_string());
     }
 
-    pub fn copy_file(&mut self) {
-        let entry_info = self
-            .selected_entry()
-            .map(|e| (e.name.clone(), e.name == ".."));
-        if let Some((name, is_parent)) = entry_info {
-            if is_parent {
-                return;
-            }
-            let path = self.current_dir.join(&name);
-            self.clipboard = Some(FileClipboard {
-                path,
-                is_cut: false,
-            });
-            self.message = Some(format!("Copied: {}", name));
+    /// Saves the current directory under `key`.
+    pub fn set_bookmark(&mut self, key: char) {
+        self.mode = Mode::Normal;
+        if !bookmarks::is_valid_key(key) {
+            self.message = Some(format!("'{}' cannot be used as a bookmark key", key));
+            return;
         }
+        let dir = self.current_dir.clone();
+        self.message = Some(match self.bookmarks.set(key, &dir) {
+            Ok(_) => format!("Bookmarked '{}': {}", key, dir.display()),
+            Err(e) => format!("Cannot save bookmark: {}", e),
+        });
     }
 
-    pub fn cut_file(&mut self) {
-        let entry_info = self
-            .selected_entry()
-            .map(|e| (e.name.clone(), e.name == ".."));
-        if let Some((name, is_parent)) = entry_info {
-            if is_parent {
-                return;
-            }
-            let path = self.current_dir.join(&name);
-            self.clipboard = Some(FileClipboard { path, is_cut: true });
-            self.message = Some(format!("Cut: {}", name));
+    pub fn start_jump_bookmark(&mut self) {
+        self.mode = Mode::BookmarkJump;
+        let keys: String = self.bookmarks.iter().map(|(k, _)| k).collect();
+        self.message = Some(if keys.is_empty() {
+            "No bookmarks yet (' for previous directory)".to_string()
+        } else {
+            format!("Jump to bookmark: {} (' for previous directory)", keys)
+        });
+    }
+
+    /// Jumps to the bookmark saved under `key`; `'` goes back like in vim.
+    pub fn jump_to_bookmark(&mut self, key: char) {
+        self.mode = Mode::Normal;
+        self.message = None;
+        if key == '\'' {
+            self.go_back();
+            return;
+        }
+        match self.bookmarks.get(key).cloned() {
+            Some(target) => self.jump_to(target),
+            None => self.message = Some(format!("No bookmark '{}'", key)),
         }
     }
 
-    pub fn paste_file(&mut self) {
-        let clip = match &self.clipboard {
-            Some(c) => c.clone(),
-            None => {
-                self.message = Some("Nothing to paste".to_string());
-                return;
-            }
+    pub fn cancel_bookmark_key(&mut self) {
+        self.mode = Mode::Normal;
+        self.message = None;
+    }
+
+    pub fn open_bookmarks(&mut self) {
+        self.mode = Mode::Bookmarks;
+        self.message = if self.bookmarks.is_empty() {
+            Some("No bookmarks yet; press m<key> in a directory to add one".to_string())
+        } else {
+            None
         };
+        let selected = self.bookmark_state.selected().unwrap_or(0);
+        self.select_bookmark_row(selected);
+    }
 
-        if !clip.path.exists() {
-            self.message = Some("Source no longer exists".to_string());
-            self.clipboard = None;
-            return;
+    pub fn close_bookmarks(&mut self) {
+        self.mode = Mode::Normal;
+        self.message = None;
+    }
+
+    pub fn bookmark_move_up(&mut self) {
+        if let Some(selected) = self.bookmark_state.selected() {
+            self.bookmark_state.select(Some(selected.saturating_sub(1)));
         }
+    }
 
-        if clip.is_cut {
-            if let Some(parent) = clip.path.parent() {
-                if parent == self.current_dir {
-                    self.message = Some("Item is already in this directory".to_string());
-                    return;
-                }
-            }
+    pub fn bookmark_move_down(&mut self) {
+        if let Some(selected) = self.bookmark_state.selected() {
+            self.select_bookmark_row(selected + 1);
         }
+    }
 
-        let file_name = match clip.path.file_name() {
-            Some(name) => name.to_string_lossy().to_string(),
-            None => {
-                self.message = Some("Invalid source path".to_string());
-                return;
-            }
-        };
+    fn select_bookmark_row(&mut self, row: usize) {
+        self.bookmark_state.select(match self.bookmarks.len() {
+            0 => None,
+            len => Some(row.min(len - 1)),
+        });
+    }
+
+    fn selected_bookmark(&self) -> Option<(char, PathBuf)> {
+        self.bookmark_state
+            .selected()
+            .and_then(|i| self.bookmarks.iter().nth(i))
+            .map(|(k, p)| (k, p.clone()))
+    }
 
-        let mut dest = self.current_dir.join(&file_name);
+    pub fn jump_to_selected_bookmark(&mut self) {
+        if let Some((_, target)) = self.selected_bookmark() {
+            self.mode = Mode::Normal;
+            self.message = None;
+            self.jump_to(target);
+        }
+    }
 
-        if clip.path.is_dir() && dest.starts_with(&clip.path) {
-            self.message = Some("Cannot copy a directory into itself".to_string());
+    pub fn delete_selected_bookmark(&mut self) {
+        let Some((key, _)) = self.selected_bookmark() else {
             return;
+        };
+        self.message = Some(match self.bookmarks.remove(key) {
+            Ok(_) => format!("Deleted bookmark '{}'", key),
+            Err(e) => format!("Cannot save bookmarks: {}", e),
+        });
+        let selected = self.bookmark_state.selected().unwrap_or(0);
+        self.select_bookmark_row(selected);
+    }
+
+    fn jump_to(&mut self, target: PathBuf) {
+        if let Err(err) = self.navigate_to(target.clone()) {
+            self.message = Some(format!("Cannot open {}: {}", target.display(), err));
         }
+    }
 
-        // Handle name conflicts
-        if dest.exists() {
-            let stem = clip
-                .path
-                .file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default();
-            let ext = clip
-                .path
-                .extension()
-                .map(|s| format!(".{}", s.to_string_lossy()))
-                .unwrap_or_default();
-            let mut counter = 1;
-            while dest.exists() {
-                dest = self
-                    .current_dir
-                    .join(format!("{}_{}{}", stem, counter, ext));
-                counter += 1;
+    // =========================================================================
+    // Search/Filter
+    // =========================================================================
+
+    pub fn start_search(&mut self) {
+        self.mode = Mode::Search;
+        // Continue editing the filter already applied
+        self.input = self.filter.chars().collect();
+        self.message = Some(self.search_hint());
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        self.message = None;
+        self.clear_filter();
+    }
+
+    pub fn confirm_search(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        // Keep the filter applied, turning typed conditions into chips
+        let query = Query::parse(&self.filter);
+        self.message = query.error;
+        for attribute in query.attributes {
+            if !self.attribute_filters.contains(&attribute) {
+                self.attribute_filters.push(attribute);
             }
         }
+        self.filter = query.name;
+    }
 
-        let result = if clip.is_cut {
-            fs::rename(&clip.path, &dest)
-        } else if clip.path.is_dir() {
-            copy_dir_recursive(&clip.path, &dest)
-        } else {
-            fs::copy(&clip.path, &dest).map(|_| ())
-        };
+    pub fn update_search(&mut self, c: char) {
+        self.input.push(c);
+        self.search_input_changed();
+    }
 
-        match result {
-            Ok(_) => {
-                let action = if clip.is_cut { "Moved" } else { "Pasted" };
-                self.message = Some(format!(
-                    "{}: {}",
-                    action,
-                    dest.file_name().unwrap_or_default().to_string_lossy()
-                ));
-                if clip.is_cut {
-                    self.clipboard = None;
-                }
-                self.invalidate_git_cache();
-                let _ = self.refresh();
-                self.update_preview();
-            }
-            Err(e) => {
-                self.message = Some(format!("Paste failed: {}", e));
-            }
+    pub fn backspace_search(&mut self) {
+        // On an empty input, drop the last condition chip instead
+        if self.input.pop().is_none() && self.attribute_filters.pop().is_none() {
+            return;
         }
+        self.search_input_changed();
     }
 
-    pub fn open_with_default(&mut self) {
-        if let Some(entry) = self.selected_entry() {
-            if entry.name == ".." {
-                return;
-            }
-            let path = self.current_dir.join(&entry.name);
+    /// Switches to the next match mode and re-applies the filter.
+    pub fn cycle_filter_mode(&mut self) {
+        self.filter_mode = self.filter_mode.next();
+        self.search_input_changed();
+    }
 
-            #[cfg(windows)]
-            let result = std::process::Command::new("cmd")
-                .args(["/C", "start", "", &path.to_string_lossy()])
-                .spawn();
+    fn search_input_changed(&mut self) {
+        self.filter = self.input.iter().collect();
+        self.message = Some(self.search_hint());
+        self.apply_filter();
+        self.update_preview();
+    }
 
-            #[cfg(not(windows))]
-            let result = std::process::Command::new("xdg-open").arg(&path).spawn();
+    fn search_hint(&self) -> String {
+        format!("Search ({}): type to filter", self.filter_mode.label())
+    }
 
-            match result {
-                Ok(_) => self.message = Some(format!("Opened: {}", entry.name)),
-                Err(e) => self.message = Some(format!("Failed to open: {}", e)),
-            }
-        }
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.apply_filter();
+        self.update_preview();
     }
 
-    pub fn start_path(&mut self) {
-        self.mode = Mode::Path;
-        self.input = self.current_dir.to_string_lossy().chars().collect();
-        self.cursor = self.input.len();
-        self.message = None;
+    pub fn clear_attribute_filters(&mut self) {
+        self.attribute_filters.clear();
+        self.apply_filter();
+        self.update_preview();
     }
 
-    pub fn confirm_path(&mut self) {
-        let path_str: String = self.input.iter().collect();
-        let path = PathBuf::from(&path_str);
+    // =========================================================================
+    // Tabs
+    // =========================================================================
 
-        if !path.exists() {
-            self.message = Some(format!("Path does not exist: {}", path_str));
+    /// Directory names of all tabs, for the tab bar.
+    pub fn tab_titles(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let dir = if i == self.active_tab {
+                    &self.current_dir
+                } else {
+                    &tab.current_dir
+                };
+                dir.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| dir.to_string_lossy().to_string())
+            })
+            .collect()
+    }
+
+    /// Opens a tab on the current directory right after the active one.
+    pub fn new_tab(&mut self) {
+        let selected = self.state.selected();
+        let tab = self.store_tab();
+        let dir = tab.current_dir.clone();
+        self.tabs[self.active_tab] = tab;
+        self.active_tab += 1;
+        self.tabs.insert(self.active_tab, Tab::default());
+
+        self.current_dir = dir;
+        self.state.select(selected);
+        if let Err(e) = self.refresh() {
+            self.message = Some(format!("Cannot read directory: {}", e));
+        }
+        self.update_preview();
+        self.message = Some(format!("Opened tab {}", self.active_tab + 1));
+    }
+
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            self.message = Some("Cannot close the last tab".to_string());
             return;
         }
+        self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        let tab = std::mem::take(&mut self.tabs[self.active_tab]);
+        self.load_tab(tab);
+    }
 
-        let target = if path.is_file() {
-            path.parent().map(|p| p.to_path_buf()).unwrap_or(path)
-        } else {
-            path
-        };
+    /// Switches to the tab at `index` (0-based).
+    pub fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        self.tabs[self.active_tab] = self.store_tab();
+        self.active_tab = index;
+        let tab = std::mem::take(&mut self.tabs[index]);
+        self.load_tab(tab);
+    }
 
-        match self.navigate_to(target) {
-            Ok(_) => self.message = None,
-            Err(e) => self.message = Some(format!("Cannot navigate: {}", e)),
+    pub fn next_tab(&mut self) {
+        self.switch_tab((self.active_tab + 1) % self.tabs.len());
+    }
+
+    pub fn prev_tab(&mut self) {
+        self.switch_tab((self.active_tab + self.tabs.len() - 1) % self.tabs.len());
+    }
+
+    /// Moves the active tab's state out of `App`.
+    fn store_tab(&mut self) -> Tab {
+        Tab {
+            current_dir: self.current_dir.clone(),
+            all_entries: std::mem::take(&mut self.all_entries),
+            filtered_indices: std::mem::take(&mut self.filtered_indices),
+            state: std::mem::take(&mut self.state),
+            scroll: self.scroll,
+            filter: std::mem::take(&mut self.filter),
+            filter_positions: std::mem::take(&mut self.filter_positions),
+            attribute_filters: std::mem::take(&mut self.attribute_filters),
+            selection: std::mem::take(&mut self.selection),
         }
     }
 
-    pub fn cancel_path(&mut self) {
-        self.mode = Mode::Normal;
-        self.input.clear();
-        self.cursor = 0;
-        self.message = None;
+    /// Moves `tab` into the `App` fields as it is.
+    fn set_tab(&mut self, tab: Tab) {
+        self.current_dir = tab.current_dir;
+        self.all_entries = tab.all_entries;
+        self.filtered_indices = tab.filtered_indices;
+        self.state = tab.state;
+        self.scroll = tab.scroll;
+        self.filter = tab.filter;
+        self.filter_positions = tab.filter_positions;
+        self.attribute_filters = tab.attribute_filters;
+        self.selection = tab.selection;
     }
 
-    pub fn start_new_file(&mut self) {
-        self.mode = Mode::NewFile;
-        self.input.clear();
-        self.cursor = 0;
+    /// Makes `tab` the active state, re-reading its directory in case it
+    /// changed while the tab was in the background.
+    fn load_tab(&mut self, tab: Tab) {
+        let scroll = tab.scroll;
+        self.set_tab(tab);
         self.message = None;
+
+        if let Err(e) = self.refresh() {
+            self.message = Some(format!("Cannot read directory: {}", e));
+        }
+        self.selection.retain(|path| path.exists());
+        self.update_preview();
+        self.scroll = scroll;
     }
 
-    pub fn start_new_folder(&mut self) {
-        self.mode = Mode::NewFolder;
-        self.input.clear();
-        self.cursor = 0;
-        self.message = None;
+    // =========================================================================
+    // Dual Pane
+    // =========================================================================
+
+    pub fn other_pane(&self) -> Option<&Tab> {
+        self.other_pane.as_ref()
     }
 
-    pub fn confirm_new_file(&mut self) {
-        let name: String = self.input.iter().collect();
-        if let Err(msg) = validate_filename(&name) {
-            self.message = Some(msg);
+    /// Shows or hides the second panel, which opens on the current directory.
+    pub fn toggle_dual_pane(&mut self) {
+        if self.other_pane.take().is_some() {
+            self.focus_right = false;
+            self.message = Some("Single panel".to_string());
             return;
         }
+        self.other_pane = Some(Tab {
+            current_dir: self.current_dir.clone(),
+            state: self.state.clone(),
+            ..Tab::default()
+        });
+        self.refresh_other_pane();
+        self.message = Some("Dual panel: Tab switches panels, c/x copy/move to the other one".to_string());
+    }
 
-        let path = self.current_dir.join(&name);
-        if path.exists() {
-            self.message = Some(format!("'{}' already exists", name));
+    /// Moves the focus to the other panel.
+    pub fn switch_pane(&mut self) {
+        let Some(other) = self.other_pane.take() else {
             return;
-        }
+        };
+        self.other_pane = Some(self.store_tab());
+        self.load_tab(other);
+        self.focus_right = !self.focus_right;
+    }
 
-        match fs::File::create(&path) {
-            Ok(_) => {
-                self.message = Some(format!("Created: {}", name));
-                self.mode = Mode::Normal;
-                self.input.clear();
-                self.cursor = 0;
-                self.invalidate_git_cache();
-                let _ = self.refresh();
-                self.update_preview();
-            }
-            Err(e) => {
-                self.message = Some(format!("Failed to create file: {}", e));
-            }
-        }
+    /// Re-reads the other panel's directory and its git status.
+    fn refresh_other_pane(&mut self) {
+        let Some(dir) = self.other_pane.as_ref().map(|p| p.current_dir.clone()) else {
+            return;
+        };
+        let _ = self.request_listing(dir.clone());
+        self.request_git_status(dir);
     }
 
-    pub fn confirm_new_folder(&mut self) {
-        let name: String = self.input.iter().collect();
-        if let Err(msg) = validate_filename(&name) {
-            self.message = Some(msg);
+    /// Copies or moves the selection (or the entry under the cursor) into
+    /// the other panel's directory, asking about conflicts like a paste.
+    pub fn transfer_to_other_pane(&mut self, kind: TransferKind) {
+        let Some(dest) = self.other_pane.as_ref().map(|p| p.current_dir.clone()) else {
+            return;
+        };
+        if dest == self.current_dir {
+            self.message = Some("Both panels show the same directory".to_string());
             return;
         }
-
-        let path = self.current_dir.join(&name);
-        if path.exists() {
-            self.message = Some(format!("'{}' already exists", name));
+        let paths = self.target_paths();
+        if paths.is_empty() {
             return;
         }
+        self.selection.clear();
+        self.pending_paste = Some(PastePlan::new(kind, dest, paths));
+        self.advance_paste();
+    }
 
-        match fs::create_dir(&path) {
-            Ok(_) => {
-                self.message = Some(format!("Created: {}", name));
-                self.mode = Mode::Normal;
-                self.input.clear();
-                self.cursor = 0;
-                self.invalidate_git_cache();
-                let _ = self.refresh();
-                self.update_preview();
-            }
-            Err(e) => {
-                self.message = Some(format!("Failed to create folder: {}", e));
+    // =========================================================================
+    // Selection
+    // =========================================================================
+
+    pub fn is_selected(&self, entry: &Entry) -> bool {
+        !self.selection.is_empty() && self.selection.contains(&self.current_dir.join(&entry.name))
+    }
+
+    /// Toggles the selection mark on the current entry and moves to the next one.
+    pub fn toggle_selection(&mut self) {
+        let name = match self.selected_entry() {
+            Some(e) if e.name != ".." => e.name.clone(),
+            _ => return,
+        };
+        let path = self.current_dir.join(name);
+        if !self.selection.remove(&path) {
+            self.selection.insert(path);
+        }
+        self.move_down();
+    }
+
+    /// Selects every visible entry (except "..").
+    pub fn select_all(&mut self) {
+        let paths: Vec<PathBuf> = self
+            .entries()
+            .filter(|e| e.name != "..")
+            .map(|e| self.current_dir.join(&e.name))
+            .collect();
+        self.selection.extend(paths);
+        self.message = Some(format!("Selected: {}", self.selection.len()));
+    }
+
+    /// Inverts the selection over the visible entries.
+    pub fn invert_selection(&mut self) {
+        let paths: Vec<PathBuf> = self
+            .entries()
+            .filter(|e| e.name != "..")
+            .map(|e| self.current_dir.join(&e.name))
+            .collect();
+        for path in paths {
+            if !self.selection.remove(&path) {
+                self.selection.insert(path);
             }
         }
+        self.message = Some(format!("Selected: {}", self.selection.len()));
     }
 
-    pub fn cancel_new(&mut self) {
-        self.mode = Mode::Normal;
-        self.input.clear();
-        self.cursor = 0;
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    /// Returns the paths an operation should act on: the selection if any,
+    /// otherwise the entry under the cursor (never "..").
+    fn target_paths(&self) -> Vec<PathBuf> {
+        if !self.selection.is_empty() {
+            let mut paths: Vec<PathBuf> = self.selection.iter().cloned().collect();
+            paths.sort();
+            return paths;
+        }
+        match self.selected_entry() {
+            Some(e) if e.name != ".." => vec![self.current_dir.join(&e.name)],
+            _ => Vec::new(),
+        }
+    }
+
+    // =========================================================================
+    // Hidden Files
+    // =========================================================================
+
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.message = Some(format!(
+            "Hidden files: {}",
+            if self.show_hidden { "shown" } else { "hidden" }
+        ));
+        self.apply_filter();
+        self.update_parent_column();
+        self.update_preview();
+    }
+
+    // =========================================================================
+    // File Operations
+    // =========================================================================
+
+    pub fn yank_path(&mut self) {
+        if let Some(path) = self.selected_path() {
+            let path_str = path.to_string_lossy().to_string();
+            match Clipboard::new().and_then(|mut cb| cb.set_text(&path_str)) {
+                Ok(_) => self.message = Some(format!("Copied: {}", path_str)),
+                Err(e) => self.message = Some(format!("Failed to copy: {}", e)),
+            }
+        }
+    }
+
+    pub fn copy_file(&mut self) {
+        self.set_clipboard(false);
+    }
+
+    pub fn cut_file(&mut self) {
+        self.set_clipboard(true);
+    }
+
+    fn set_clipboard(&mut self, is_cut: bool) {
+        let paths = self.target_paths();
+        if paths.is_empty() {
+            return;
+        }
+        let action = if is_cut { "Cut" } else { "Copied" };
+        self.message = Some(match paths.as_slice() {
+            [single] => format!("{}: {}", action, display_name(single)),
+            _ => format!("{}: {} items", action, paths.len()),
+        });
+        self.clipboard = Some(FileClipboard { paths, is_cut });
+        self.selection.clear();
+    }
+
+    /// Starts pasting the clipboard into the current directory. Items whose
+    /// name is taken pause the paste in conflict mode until resolved.
+    pub fn paste_file(&mut self) {
+        let clip = match &self.clipboard {
+            Some(c) => c.clone(),
+            None => {
+                self.message = Some("Nothing to paste".to_string());
+                return;
+            }
+        };
+
+        let kind = if clip.is_cut {
+            TransferKind::Move
+        } else {
+            TransferKind::Copy
+        };
+        let mut plan = PastePlan::new(kind, self.current_dir.clone(), clip.paths);
+        plan.from_clipboard = true;
+        self.pending_paste = Some(plan);
+        self.advance_paste();
+    }
+
+    /// Continues the pending paste until the next conflict or the end.
+    fn advance_paste(&mut self) {
+        let Some(mut plan) = self.pending_paste.take() else {
+            return;
+        };
+        if !plan.advance() {
+            self.pending_paste = Some(plan);
+            self.mode = Mode::Conflict;
+            self.message = None;
+            return;
+        }
+        self.mode = Mode::Normal;
+        self.submit_paste(plan);
+    }
+
+    /// Hands a fully resolved paste to the transfer worker.
+    fn submit_paste(&mut self, plan: PastePlan) {
+        let is_cut = plan.kind == TransferKind::Move;
+        if is_cut && plan.from_clipboard {
+            // Keep only the items that could not be queued
+            let remaining: Vec<PathBuf> = plan
+                .failed
+                .iter()
+                .map(|(p, _)| p.clone())
+                .filter(|p| p.exists())
+                .collect();
+            self.clipboard = if remaining.is_empty() {
+                None
+            } else {
+                Some(FileClipboard {
+                    paths: remaining,
+                    is_cut: true,
+                })
+            };
+        }
+
+        let queued = plan.ready.len();
+        if queued > 0
+            && self
+                .transfers
+                .submit(plan.kind, plan.ready, self.copy_policy)
+                .is_none()
+        {
+            self.message = Some("Paste failed: transfer worker stopped".to_string());
+            return;
+        }
+
+        let action = if is_cut { "Moving" } else { "Copying" };
+        let skipped = if plan.skipped > 0 {
+            format!(", {} skipped", plan.skipped)
+        } else {
+            String::new()
+        };
+        self.message = Some(match plan.failed.as_slice() {
+            [] if queued == 0 => format!("Nothing pasted{}", skipped),
+            [] => format!("{} {} item(s) in background{}", action, queued, skipped),
+            [(_, err)] if queued == 0 && plan.skipped == 0 => err.clone(),
+            [(src, err), ..] => format!(
+                "{} {} item(s){}, {} failed ({}: {})",
+                action,
+                queued,
+                skipped,
+                plan.failed.len(),
+                display_name(src),
+                err
+            ),
+        });
+    }
+
+    // =========================================================================
+    // Paste Conflicts
+    // =========================================================================
+
+    pub fn resolve_conflict(&mut self, choice: ConflictChoice) {
+        if let Some(plan) = self.pending_paste.as_mut() {
+            plan.resolve(choice);
+        }
+        self.advance_paste();
+    }
+
+    pub fn toggle_conflict_apply_to_all(&mut self) {
+        if let Some(plan) = self.pending_paste.as_mut() {
+            plan.apply_to_all = !plan.apply_to_all;
+        }
+    }
+
+    pub fn start_conflict_rename(&mut self) {
+        if let Some(plan) = &self.pending_paste {
+            self.input = plan.suggested_name().chars().collect();
+            self.cursor = self.input.len();
+            self.mode = Mode::ConflictRename;
+            self.message = None;
+        }
+    }
+
+    pub fn confirm_conflict_rename(&mut self) {
+        let name: String = self.input.iter().collect();
+        if let Err(msg) = validate_filename(&name) {
+            self.message = Some(msg);
+            return;
+        }
+        let Some(plan) = self.pending_paste.as_mut() else {
+            return;
+        };
+        if let Err(msg) = plan.resolve_with_name(&name) {
+            self.message = Some(msg);
+            return;
+        }
+        self.input.clear();
+        self.cursor = 0;
+        self.advance_paste();
+    }
+
+    pub fn cancel_conflict_rename(&mut self) {
+        self.mode = Mode::Conflict;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+    }
+
+    /// Abandons the pending paste without transferring anything.
+    pub fn cancel_paste(&mut self) {
+        self.pending_paste = None;
+        self.mode = Mode::Normal;
+        self.message = Some("Paste cancelled".to_string());
+    }
+
+    // =========================================================================
+    // Background Transfers
+    // =========================================================================
+
+    /// Housekeeping run on every event loop iteration.
+    pub fn tick(&mut self) {
+        let preview = self.selected_path();
+        if let Some(watcher) = &mut self.watcher {
+            watcher.watch(&self.current_dir, preview.as_deref());
+        }
+        if matches!(self.mode, Mode::Normal | Mode::Search) {
+            if let Some((dir, changes)) = self.deferred_changes.take() {
+                self.apply_file_changes(dir, changes);
+            }
+        }
+    }
+
+    /// Reports a finished transfer job, records it for undo and reloads the
+    /// listings it changed.
+    pub fn apply_transfer(&mut self, outcome: JobOutcome) {
+        self.quit_requested = false;
+        if let Some((_, label)) = self.journal_job.take_if(|(id, _)| *id == outcome.id) {
+            let step = self.journal.resume(outcome.check());
+            self.run_journal_step(label, Some(step));
+            self.refresh_other_pane();
+            return;
+        }
+        self.message = Some(summarize_transfer(&outcome));
+        if !outcome.completed.is_empty() {
+            let op = match outcome.kind {
+                TransferKind::Copy => Operation::Copy(outcome.completed),
+                TransferKind::Move => Operation::Move(outcome.completed),
+            };
+            self.journal.record(if outcome.replaced.is_empty() {
+                op
+            } else {
+                Operation::Batch(vec![Operation::Trash(outcome.replaced), op])
+            });
+        }
+
+        self.invalidate_git_cache();
+        let _ = self.refresh();
+        self.update_preview();
+        self.refresh_other_pane();
+    }
+
+    /// Reloads the listing and preview of `dir` after other programs
+    /// changed them, keeping the cursor on the same entry. Prompts are left
+    /// alone; their changes are picked up once back in the file list.
+    pub fn apply_file_changes(&mut self, dir: PathBuf, changes: Changes) {
+        if dir != self.current_dir {
+            return;
+        }
+        if !matches!(self.mode, Mode::Normal | Mode::Search) {
+            let deferred = match self.deferred_changes.take() {
+                Some((deferred_dir, deferred)) if deferred_dir == dir => Changes {
+                    listing: deferred.listing || changes.listing,
+                    preview: deferred.preview || changes.preview,
+                },
+                _ => changes,
+            };
+            self.deferred_changes = Some((dir, deferred));
+            return;
+        }
+
+        // With workers, a new listing updates the preview when it arrives
+        let mut same_entry = true;
+        if changes.listing {
+            let selected = self.selected_path();
+            self.invalidate_git_cache();
+            if let Err(e) = self.refresh() {
+                self.message = Some(format!("Cannot read directory: {}", e));
+                return;
+            }
+            same_entry = selected.is_some() && self.selected_path() == selected;
+        }
+        if changes.preview || !same_entry {
+            if let (true, Some(path)) = (changes.preview, self.selected_path()) {
+                self.preview_cache.remove(&path);
+            }
+            let scroll = self.scroll;
+            self.update_preview();
+            if same_entry {
+                self.scroll = scroll;
+            }
+        }
+    }
+
+    /// Returns true if the app may quit. While transfers are running the
+    /// first request only warns; a second one quits anyway.
+    pub fn request_quit(&mut self) -> bool {
+        if !self.transfers.is_busy() || self.quit_requested {
+            return true;
+        }
+        self.quit_requested = true;
+        self.message = Some("Transfer in progress - press q again to quit anyway".to_string());
+        false
+    }
+
+    pub fn cancel_transfer(&mut self) {
+        if self.transfers.progress().is_some() {
+            self.transfers.cancel();
+            self.message = Some("Cancelling transfer...".to_string());
+        } else {
+            self.message = Some("No transfer running".to_string());
+        }
+    }
+
+    pub fn toggle_transfer_pause(&mut self) {
+        let paused = self.transfers.toggle_pause();
+        self.message = Some(
+            if paused {
+                "Transfers paused"
+            } else {
+                "Transfers resumed"
+            }
+            .to_string(),
+        );
+    }
+
+    pub fn open_with_default(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            if entry.name == ".." {
+                return;
+            }
+            let path = self.current_dir.join(&entry.name);
+
+            #[cfg(windows)]
+            let result = std::process::Command::new("cmd")
+                .args(["/C", "start", "", &path.to_string_lossy()])
+                .spawn();
+
+            #[cfg(not(windows))]
+            let result = std::process::Command::new("xdg-open").arg(&path).spawn();
+
+            match result {
+                Ok(_) => self.message = Some(format!("Opened: {}", entry.name)),
+                Err(e) => self.message = Some(format!("Failed to open: {}", e)),
+            }
+        }
+    }
+
+    // =========================================================================
+    // Frecency Jump
+    // =========================================================================
+
+    pub fn start_jump(&mut self) {
+        self.mode = Mode::Jump;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+        self.update_jump_matches();
+    }
+
+    /// Re-runs the query after the input changed. Input that starts like a
+    /// path hands over to the path prompt.
+    pub fn update_jump_matches(&mut self) {
+        if matches!(self.input.first(), Some('/' | '.' | '~' | '$')) {
+            self.mode = Mode::Path;
+            self.jump_matches.clear();
+            self.update_path_suggestions();
+            return;
+        }
+        let query: String = self.input.iter().collect();
+        self.jump_matches = self.frecency.query(&query, &self.current_dir);
+        self.jump_state
+            .select((!self.jump_matches.is_empty()).then_some(0));
+    }
+
+    pub fn jump_move_up(&mut self) {
+        if let Some(i) = self.jump_state.selected() {
+            self.jump_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn jump_move_down(&mut self) {
+        if let Some(i) = self.jump_state.selected() {
+            if i + 1 < self.jump_matches.len() {
+                self.jump_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn confirm_jump(&mut self) {
+        let Some(target) = self
+            .jump_state
+            .selected()
+            .and_then(|i| self.jump_matches.get(i))
+            .cloned()
+        else {
+            self.message = Some("No matching directory".to_string());
+            return;
+        };
+
+        if let Err(err) = self.navigate_to(target.clone()) {
+            // Forget directories that can no longer be entered
+            let _ = self.frecency.remove(&target);
+            self.update_jump_matches();
+            self.message = Some(format!("Cannot open {}: {}", target.display(), err));
+        }
+    }
+
+    pub fn cancel_jump(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        self.cursor = 0;
+        self.jump_matches.clear();
+        self.message = None;
+    }
+
+    // =========================================================================
+    // File Finder
+    // =========================================================================
+
+    /// Opens the finder on the current directory and starts walking it.
+    pub fn start_find(&mut self) {
+        self.mode = Mode::Find;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+        self.find_results.clear();
+        self.find_state.select(None);
+        self.search_id += 1;
+        self.finder = Some(Finder::start(
+            self.search_id,
+            self.current_dir.clone(),
+            self.show_hidden,
+            self.events.clone(),
+        ));
+    }
+
+    /// Adds paths the finder walked and re-ranks the results.
+    pub fn apply_finder_paths(&mut self, id: u64, paths: Vec<String>, done: bool) {
+        let Some(finder) = self.finder.as_mut().filter(|finder| finder.id == id) else {
+            return;
+        };
+        let found = !paths.is_empty();
+        finder.add(paths, done);
+        if found && self.mode == Mode::Find {
+            self.update_find_results();
+        }
+    }
+
+    pub fn update_find_results(&mut self) {
+        let Some(finder) = &self.finder else {
+            return;
+        };
+        let query: String = self.input.iter().collect();
+        self.find_results = finder.search(&query);
+        let selected = self.find_state.selected().unwrap_or(0);
+        self.find_state.select(if self.find_results.is_empty() {
+            None
+        } else {
+            Some(selected.min(self.find_results.len() - 1))
+        });
+    }
+
+    pub fn find_move_up(&mut self) {
+        if let Some(i) = self.find_state.selected() {
+            self.find_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn find_move_down(&mut self) {
+        if let Some(i) = self.find_state.selected() {
+            if i + 1 < self.find_results.len() {
+                self.find_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    /// Goes to the directory of the chosen result and selects it there.
+    pub fn confirm_find(&mut self) {
+        let (Some(finder), Some(result)) = (
+            &self.finder,
+            self.find_state.selected().and_then(|i| self.find_results.get(i)),
+        ) else {
+            return;
+        };
+        let path = finder.root.join(&result.path);
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return;
+        };
+        let name = name.to_string_lossy().to_string();
+
+        match self.navigate_to_entry(dir, name) {
+            Ok(_) => {
+                self.finder = None;
+                self.find_results.clear();
+            }
+            Err(e) => self.message = Some(format!("Cannot open {}: {}", dir.display(), e)),
+        }
+    }
+
+    pub fn cancel_find(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        self.cursor = 0;
+        // Dropping the finder stops the walk
+        self.finder = None;
+        self.find_results.clear();
+        self.message = None;
+    }
+
+    // =========================================================================
+    // Content Search
+    // =========================================================================
+
+    /// Opens the content search. The previous search is shown again if it
+    /// covered the current directory.
+    pub fn start_grep(&mut self) {
+        let keep = self
+            .grep
+            .as_ref()
+            .is_some_and(|g| self.current_dir.starts_with(&g.root));
+        if !keep {
+            self.grep = None;
+            self.grep_state.select(None);
+        }
+        self.mode = Mode::Grep;
+        self.input = self
+            .grep
+            .as_ref()
+            .map(|g| g.query.chars().collect())
+            .unwrap_or_default();
+        self.cursor = self.input.len();
+        self.message = None;
+    }
+
+    /// Adds matches the content search found.
+    pub fn apply_grep_matches(&mut self, id: u64, matches: Vec<GrepMatch>, done: bool) {
+        let Some(grep) = self.grep.as_mut().filter(|grep| grep.id == id) else {
+            return;
+        };
+        grep.add(matches, done);
+        if self.grep_state.selected().is_none() && !grep.matches.is_empty() {
+            self.grep_state.select(Some(0));
+        }
+    }
+
+    /// Starts a new search if the query changed, otherwise opens the
+    /// selected result.
+    pub fn confirm_grep(&mut self) {
+        let query: String = self.input.iter().collect();
+        if self.grep.as_ref().is_some_and(|g| g.query == query) {
+            self.open_grep_match();
+            return;
+        }
+        if query.is_empty() {
+            self.message = Some("Type the text to search for".to_string());
+            return;
+        }
+        let root = self
+            .grep
+            .take()
+            .map(|g| g.root.clone())
+            .unwrap_or_else(|| self.current_dir.clone());
+        self.search_id += 1;
+        self.grep = Some(ContentSearch::start(
+            self.search_id,
+            root,
+            query,
+            self.show_hidden,
+            self.events.clone(),
+        ));
+        self.grep_state.select(None);
+        self.message = None;
+    }
+
+    pub fn grep_move_up(&mut self) {
+        if let Some(i) = self.grep_state.selected() {
+            self.grep_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn grep_move_down(&mut self) {
+        let count = self.grep.as_ref().map_or(0, |g| g.matches.len());
+        if let Some(i) = self.grep_state.selected() {
+            if i + 1 < count {
+                self.grep_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    /// Selects the file of the chosen match and previews it from that line,
+    /// with the match highlighted.
+    fn open_grep_match(&mut self) {
+        let Some((path, line, range)) = self.grep.as_ref().and_then(|g| {
+            let m = g.matches.get(self.grep_state.selected()?)?;
+            Some((g.root.join(&m.path), m.line, m.range.clone()))
+        }) else {
+            return;
+        };
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return;
+        };
+        let name = name.to_string_lossy().to_string();
+        let dir = match dir.canonicalize() {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.message = Some(format!("Cannot open {}: {}", dir.display(), e));
+                return;
+            }
+        };
+
+        // The preview is loaded once the file is selected, which in another
+        // directory waits for its listing
+        self.grep_target = Some((dir.join(&name), line, range));
+        if dir == self.current_dir {
+            self.mode = Mode::Normal;
+            self.input.clear();
+            if !self.select_entry_named(&name) {
+                self.grep_target = None;
+                self.message = Some(format!("'{}' is no longer there", name));
+                return;
+            }
+            self.update_preview();
+        } else if let Err(e) = self.navigate_to_entry(&dir, name.clone()) {
+            self.grep_target = None;
+            self.message = Some(format!("Cannot open {}: {}", dir.display(), e));
+            return;
+        }
+        self.message = Some(format!("{}:{}", name, line));
+    }
+
+    /// Closes the results; the search is kept for the next `start_grep`.
+    pub fn cancel_grep(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+    }
+
+    // =========================================================================
+    // Path Input
+    // =========================================================================
+
+    pub fn start_path(&mut self) {
+        self.mode = Mode::Path;
+        let mut path = self.current_dir.to_string_lossy().to_string();
+        if !path.ends_with(std::path::MAIN_SEPARATOR) {
+            path.push(std::path::MAIN_SEPARATOR);
+        }
+        self.input = path.chars().collect();
+        self.cursor = self.input.len();
+        self.message = None;
+        self.update_path_suggestions();
+    }
+
+    /// Lists the child directories matching the partial name being typed.
+    pub fn update_path_suggestions(&mut self) {
+        let input: String = self.input.iter().collect();
+        self.path_suggestions = completion::directory_completions(&input, &self.current_dir);
+        self.path_state
+            .select((!self.path_suggestions.is_empty()).then_some(0));
+    }
+
+    pub fn path_move_up(&mut self) {
+        if let Some(i) = self.path_state.selected() {
+            self.path_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn path_move_down(&mut self) {
+        if let Some(i) = self.path_state.selected() {
+            if i + 1 < self.path_suggestions.len() {
+                self.path_state.select(Some(i + 1));
+            }
+        }
+    }
+
+    /// Extends the partial name to the longest prefix all suggestions share;
+    /// if that adds nothing, completes the highlighted suggestion.
+    pub fn complete_path(&mut self) {
+        let input: String = self.input.iter().collect();
+        let (dir_part, partial) = completion::split_input(&input);
+
+        let prefix = completion::common_prefix(&self.path_suggestions);
+        let completed = if prefix.chars().count() > partial.chars().count() {
+            format!("{}{}", dir_part, prefix)
+        } else if let Some(name) = self
+            .path_state
+            .selected()
+            .and_then(|i| self.path_suggestions.get(i))
+        {
+            format!("{}{}{}", dir_part, name, std::path::MAIN_SEPARATOR)
+        } else {
+            return;
+        };
+
+        self.input = completed.chars().collect();
+        self.cursor = self.input.len();
+        self.update_path_suggestions();
+    }
+
+    pub fn confirm_path(&mut self) {
+        let path_str: String = self.input.iter().collect();
+        // Relative paths are relative to the directory being browsed
+        let path = match completion::expand_path(&path_str, &self.current_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                self.message = Some(e);
+                return;
+            }
+        };
+
+        if !path.exists() {
+            self.message = Some(match completion::closest_existing_ancestor(&path) {
+                Some(ancestor) => format!(
+                    "{} does not exist; {} is the closest existing directory",
+                    path.display(),
+                    ancestor.display()
+                ),
+                None => format!("Path does not exist: {}", path.display()),
+            });
+            return;
+        }
+
+        let target = if path.is_file() {
+            path.parent().map(|p| p.to_path_buf()).unwrap_or(path)
+        } else {
+            path
+        };
+
+        match self.navigate_to(target) {
+            Ok(_) => {
+                self.message = None;
+                self.path_suggestions.clear();
+            }
+            Err(e) => self.message = Some(format!("Cannot navigate: {}", e)),
+        }
+    }
+
+    pub fn cancel_path(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        self.cursor = 0;
+        self.path_suggestions.clear();
+        self.message = None;
+    }
+
+    // =========================================================================
+    // Create/Rename
+    // =========================================================================
+
+    pub fn start_new_file(&mut self) {
+        self.mode = Mode::NewFile;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+    }
+
+    pub fn start_new_folder(&mut self) {
+        self.mode = Mode::NewFolder;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+    }
+
+    pub fn confirm_new_file(&mut self) {
+        let name: String = self.input.iter().collect();
+        if let Err(msg) = validate_filename(&name) {
+            self.message = Some(msg);
+            return;
+        }
+
+        let path = self.current_dir.join(&name);
+        if path.exists() {
+            self.message = Some(format!("'{}' already exists", name));
+            return;
+        }
+
+        match fs::File::create(&path) {
+            Ok(_) => {
+                self.journal.record(Operation::Create {
+                    path,
+                    is_dir: false,
+                });
+                self.message = Some(format!("Created: {}", name));
+                self.mode = Mode::Normal;
+                self.input.clear();
+                self.cursor = 0;
+                self.invalidate_git_cache();
+                let _ = self.refresh();
+                self.update_preview();
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to create file: {}", e));
+            }
+        }
+    }
+
+    pub fn confirm_new_folder(&mut self) {
+        let name: String = self.input.iter().collect();
+        if let Err(msg) = validate_filename(&name) {
+            self.message = Some(msg);
+            return;
+        }
+
+        let path = self.current_dir.join(&name);
+        if path.exists() {
+            self.message = Some(format!("'{}' already exists", name));
+            return;
+        }
+
+        match fs::create_dir(&path) {
+            Ok(_) => {
+                self.journal.record(Operation::Create { path, is_dir: true });
+                self.message = Some(format!("Created: {}", name));
+                self.mode = Mode::Normal;
+                self.input.clear();
+                self.cursor = 0;
+                self.invalidate_git_cache();
+                let _ = self.refresh();
+                self.update_preview();
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to create folder: {}", e));
+            }
+        }
+    }
+
+    pub fn cancel_new(&mut self) {
+        self.mode = Mode::Normal;
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+    }
+
+    pub fn start_rename(&mut self) {
+        let entry_info = self
+            .selected_entry()
+            .map(|e| (e.name.clone(), e.name == ".."));
+
+        if let Some((name, is_parent)) = entry_info {
+            if is_parent {
+                self.message = Some("Cannot rename '..'".to_string());
+                return;
+            }
+            self.mode = Mode::Rename;
+            self.input = name.chars().collect();
+            self.cursor = self.input.len();
+            self.message = None;
+        }
+    }
+
+    /// Names a bulk rename works on: the selection, or all visible items.
+    fn bulk_rename_targets(&self) -> Vec<String> {
+        self.entries()
+            .filter(|e| e.name != "..")
+            .filter(|e| self.selection.is_empty() || self.is_selected(e))
+            .map(|e| e.name.clone())
+            .collect()
+    }
+
+    /// Opens the selected (or all visible) names in $EDITOR for renaming.
+    pub fn start_bulk_rename(&mut self) {
+        let names = self.bulk_rename_targets();
+        if names.is_empty() {
+            self.message = Some("Nothing to rename".to_string());
+            return;
+        }
+
+        match EditSession::create(&self.current_dir, names) {
+            Ok(session) => {
+                self.edit_session = Some(session);
+                self.editor_pending = true;
+            }
+            Err(msg) => self.message = Some(msg),
+        }
+    }
+
+    /// Command the event loop should run with the terminal suspended.
+    pub fn take_editor_command(&mut self) -> Option<std::process::Command> {
+        if !std::mem::take(&mut self.editor_pending) {
+            return None;
+        }
+        self.edit_session.as_ref().map(EditSession::editor_command)
+    }
+
+    /// Reads back the edited names once the editor has exited.
+    pub fn finish_bulk_rename_edit(&mut self, status: io::Result<std::process::ExitStatus>) {
+        let Some(session) = self.edit_session.take() else {
+            return;
+        };
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                session.discard();
+                self.message = Some(format!("Editor exited with {}; nothing renamed", status));
+                return;
+            }
+            Err(e) => {
+                session.discard();
+                self.message = Some(format!("Cannot start editor: {}", e));
+                return;
+            }
+        }
+
+        match session.finish() {
+            Ok(plan) if plan.renames.is_empty() => {
+                self.message = Some("No names changed".to_string());
+            }
+            Ok(plan) => {
+                self.message = Some(format!("Rename {} item(s)? (y/n)", plan.renames.len()));
+                self.rename_plan = Some(plan);
+                self.mode = Mode::ConfirmBulkRename;
+                self.scroll = 0;
+            }
+            Err(msg) => self.message = Some(msg),
+        }
+    }
+
+    pub fn confirm_bulk_rename(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(plan) = self.rename_plan.take() {
+            self.apply_rename_plan(plan);
+        }
+    }
+
+    fn apply_rename_plan(&mut self, plan: RenamePlan) {
+        match plan.execute() {
+            Ok(pairs) => {
+                self.message = Some(format!("Renamed {} item(s)", pairs.len()));
+                self.journal.record(Operation::Rename(pairs));
+                self.selection.clear();
+                self.invalidate_git_cache();
+                let _ = self.refresh();
+                self.update_preview();
+            }
+            Err(e) => {
+                self.message = Some(format!("Bulk rename failed, nothing renamed: {}", e));
+            }
+        }
+    }
+
+    pub fn cancel_bulk_rename(&mut self) {
+        self.rename_plan = None;
+        self.mode = Mode::Normal;
+        self.message = Some("Bulk rename cancelled".to_string());
+    }
+
+    /// Starts a find/replace rename over the selected (or all visible) names.
+    pub fn start_pattern_rename(&mut self) {
+        let names = self.bulk_rename_targets();
+        if names.is_empty() {
+            self.message = Some("Nothing to rename".to_string());
+            return;
+        }
+        self.pattern_preview = names.into_iter().map(|n| (n.clone(), n)).collect();
+        self.pattern_problems = vec![None; self.pattern_preview.len()];
+        self.mode = Mode::PatternRename;
+        self.input.clear();
+        self.cursor = 0;
+        self.scroll = 0;
+        self.message = None;
+    }
+
+    /// Recomputes the new names after the pattern was edited.
+    pub fn update_pattern_preview(&mut self) {
+        let input: String = self.input.iter().collect();
+        let pattern = match RenamePattern::parse(&input) {
+            Ok(pattern) => pattern,
+            Err(msg) => {
+                self.message = Some(msg);
+                return;
+            }
+        };
+        self.message = None;
+        // The counter only advances for names the pattern renames
+        let mut number = 0;
+        for (old, new) in self.pattern_preview.iter_mut() {
+            if pattern.matches(old) {
+                number += 1;
+            }
+            *new = pattern.apply(old, number);
+        }
+        self.pattern_problems = rename::check_renames(&self.current_dir, &self.pattern_preview);
+    }
+
+    pub fn confirm_pattern_rename(&mut self) {
+        let input: String = self.input.iter().collect();
+        if let Err(msg) = RenamePattern::parse(&input) {
+            self.message = Some(msg);
+            return;
+        }
+        let invalid = self.pattern_problems.iter().filter(|p| p.is_some()).count();
+        if invalid > 0 {
+            self.message = Some(format!("{} name(s) are invalid; adjust the pattern", invalid));
+            return;
+        }
+
+        // Keep the preview, so the pattern can be fixed if the plan is refused
+        let plan = match RenamePlan::new(&self.current_dir, self.pattern_preview.clone()) {
+            Ok(plan) => plan,
+            Err(msg) => {
+                self.message = Some(msg);
+                return;
+            }
+        };
+        self.cancel_pattern_rename();
+        if plan.renames.is_empty() {
+            self.message = Some("No names changed".to_string());
+        } else {
+            self.apply_rename_plan(plan);
+        }
+    }
+
+    pub fn cancel_pattern_rename(&mut self) {
+        self.mode = Mode::Normal;
+        self.pattern_preview.clear();
+        self.pattern_problems.clear();
+        self.input.clear();
+        self.cursor = 0;
+        self.message = None;
+    }
+
+    pub fn toggle_help(&mut self) {
+        if self.mode == Mode::Help {
+            self.mode = Mode::Normal;
+        } else {
+            self.mode = Mode::Help;
+        }
+    }
+
+    // =========================================================================
+    // Input/Cursor Handling
+    // =========================================================================
+
+    pub fn input_char(&mut self, c: char) {
+        self.input.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    pub fn input_backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.input.remove(self.cursor);
+        }
+    }
+
+    pub fn input_delete(&mut self) {
+        if self.cursor < self.input.len() {
+            self.input.remove(self.cursor);
+        }
+    }
+
+    pub fn cursor_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn cursor_right(&mut self) {
+        if self.cursor < self.input.len() {
+            self.cursor += 1;
+        }
+    }
+
+    pub fn cursor_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn cursor_end(&mut self) {
+        self.cursor = self.input.len();
+    }
+
+    pub fn input_clear(&mut self) {
+        self.input.clear();
+        self.cursor = 0;
+    }
+
+    pub fn confirm_rename(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            let new_name: String = self.input.iter().collect();
+            let old_path = self.current_dir.join(&entry.name);
+            let new_path = self.current_dir.join(&new_name);
+
+            if let Err(msg) = validate_filename(&new_name) {
+                self.message = Some(msg);
+                return;
+            }
+
+            if old_path == new_path {
+                self.mode = Mode::Normal;
+                self.input.clear();
+                self.message = None;
+                return;
+            }
+
+            match fs::rename(&old_path, &new_path) {
+                Ok(_) => {
+                    self.journal
+                        .record(Operation::Rename(vec![(old_path, new_path)]));
+                    self.message = Some(format!("Renamed to: {}", new_name));
+                    self.mode = Mode::Normal;
+                    self.input.clear();
+                    self.invalidate_git_cache();
+                    let _ = self.refresh();
+                    self.update_preview();
+                }
+                Err(e) => {
+                    self.message = Some(format!("Rename failed: {}", e));
+                }
+            }
+        }
+    }
+
+    pub fn start_delete(&mut self) {
+        self.start_delete_with(Mode::ConfirmDelete);
+    }
+
+    pub fn start_permanent_delete(&mut self) {
+        self.start_delete_with(Mode::ConfirmPermanentDelete);
+    }
+
+    fn start_delete_with(&mut self, mode: Mode) {
+        let targets = self.target_paths();
+        let what = match targets.as_slice() {
+            [] => {
+                if self.selected_entry().is_some_and(|e| e.name == "..") {
+                    self.message = Some("Cannot delete '..'".to_string());
+                }
+                return;
+            }
+            [single] => format!("'{}'", display_name(single)),
+            _ => format!("{} items", targets.len()),
+        };
+        self.message = Some(if mode == Mode::ConfirmPermanentDelete {
+            format!("PERMANENTLY delete {}? This cannot be undone; type Y to confirm", what)
+        } else {
+            format!("Move {} to trash? (y/n)", what)
+        });
+        self.mode = mode;
+    }
+
+    /// Moves the targets to the trash.
+    pub fn confirm_delete(&mut self) {
+        self.delete_targets(false);
+    }
+
+    /// Removes the targets from disk without going through the trash.
+    pub fn confirm_permanent_delete(&mut self) {
+        self.delete_targets(true);
+    }
+
+    fn delete_targets(&mut self, permanent: bool) {
+        let targets = self.target_paths();
+        let mut deleted = 0;
+        let mut trashed: Vec<TrashItem> = Vec::new();
+        let mut failed: Vec<(PathBuf, io::Error)> = Vec::new();
+
+        for path in &targets {
+            let result = if !permanent {
+                trash::move_to_trash(path).map(|item| trashed.push(item))
+            } else if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            match result {
+                Ok(_) => {
+                    deleted += 1;
+                    self.selection.remove(path);
+                }
+                Err(e) => failed.push((path.clone(), e)),
+            }
+        }
+
+        let action = if permanent { "Deleted" } else { "Moved to trash" };
+        self.message = Some(match failed.as_slice() {
+            [] if deleted == 1 => format!("{}: {}", action, display_name(&targets[0])),
+            [] => format!("{}: {} items", action, deleted),
+            [(_, e)] if targets.len() == 1 => format!("Delete failed: {}", e),
+            [(path, e), ..] => format!(
+                "{} {} of {} items, {} failed ({}: {})",
+                action,
+                deleted,
+                targets.len(),
+                failed.len(),
+                display_name(path),
+                e
+            ),
+        });
+
+        if !trashed.is_empty() {
+            self.journal.record(Operation::Trash(trashed));
+        }
+
+        if deleted > 0 {
+            self.invalidate_git_cache();
+            // Keep the cursor near where it was once the new listing arrives
+            let failed: Vec<PathBuf> = failed.into_iter().map(|(path, _)| path).collect();
+            let removed: HashSet<&PathBuf> =
+                targets.iter().filter(|p| !failed.contains(p)).collect();
+            let neighbours = self.surviving_neighbours(&removed);
+            self.listing_select = Some((self.current_dir.clone(), neighbours));
+            let _ = self.refresh();
+            self.update_preview();
+        }
+        self.mode = Mode::Normal;
+    }
+
+    /// Names of the first entries below and above the cursor, starting at
+    /// the cursor itself, that are not in `removed`.
+    fn surviving_neighbours(&self, removed: &HashSet<&PathBuf>) -> Vec<String> {
+        let Some(selected) = self.state.selected() else {
+            return Vec::new();
+        };
+        let names: Vec<&String> = self
+            .filtered_indices
+            .iter()
+            .map(|&i| &self.all_entries[i].name)
+            .collect();
+        let survives = |name: &&&String| !removed.contains(&self.current_dir.join(name));
+        let below = names.iter().skip(selected).find(survives);
+        let above = names.iter().take(selected).rev().find(survives);
+        below.into_iter().chain(above).map(|name| name.to_string()).collect()
+    }
+
+    pub fn cancel_delete(&mut self) {
+        self.mode = Mode::Normal;
+        self.message = None;
+    }
+
+    // =========================================================================
+    // Undo/Redo
+    // =========================================================================
+
+    pub fn start_undo(&mut self) {
+        if self.journal.is_busy() {
+            self.message = Some("Wait for the running undo or redo to finish".to_string());
+            return;
+        }
+        match self.journal.peek_undo() {
+            Some(op) => {
+                self.message = Some(format!("Undo: {}? (y/n)", op.describe_undo()));
+                self.mode = Mode::ConfirmUndo;
+            }
+            None => self.message = Some("Nothing to undo".to_string()),
+        }
+    }
+
+    pub fn start_redo(&mut self) {
+        if self.journal.is_busy() {
+            self.message = Some("Wait for the running undo or redo to finish".to_string());
+            return;
+        }
+        match self.journal.peek_redo() {
+            Some(op) => {
+                self.message = Some(format!("Redo: {}? (y/n)", op.describe_redo()));
+                self.mode = Mode::ConfirmRedo;
+            }
+            None => self.message = Some("Nothing to redo".to_string()),
+        }
+    }
+
+    pub fn confirm_undo(&mut self) {
+        self.mode = Mode::Normal;
+        let step = self.journal.undo();
+        self.run_journal_step("Undo", step);
+    }
+
+    pub fn confirm_redo(&mut self) {
+        self.mode = Mode::Normal;
+        let step = self.journal.redo();
+        self.run_journal_step("Redo", step);
+    }
+
+    pub fn cancel_journal_step(&mut self) {
+        self.mode = Mode::Normal;
+        self.message = None;
+    }
+
+    /// Moves and copies of an undo or redo run on the transfer worker; the
+    /// journal continues once `apply_transfer` gets their outcome.
+    fn run_journal_step(&mut self, label: &'static str, mut step: Option<Step>) {
+        let result = loop {
+            match step {
+                Some(Step::Transfer(kind, items)) => {
+                    match self.transfers.submit(kind, items, CopyPolicy::default()) {
+                        Some(id) => {
+                            self.journal_job = Some((id, label));
+                            self.message = Some(format!("{} running in the background", label));
+                            return;
+                        }
+                        None => {
+                            let stopped = io::Error::other("transfer worker stopped");
+                            step = Some(self.journal.resume(Err(stopped)));
+                        }
+                    }
+                }
+                Some(Step::Done(result)) => break Some(result),
+                None => break None,
+            }
+        };
+        self.finish_journal_step(label, result);
+    }
+
+    fn finish_journal_step(&mut self, label: &str, result: Option<io::Result<String>>) {
+        self.message = match result {
+            Some(Ok(description)) => Some(format!("{}: {}", label, description)),
+            Some(Err(e)) => Some(format!("{} failed: {}", label, e)),
+            None => None,
+        };
+        self.selection.clear();
+        self.invalidate_git_cache();
+        let _ = self.refresh();
+        self.update_preview();
+    }
+
+    // =========================================================================
+    // Sorting
+    // =========================================================================
+
+    /// The sort used for `dir`.
+    pub fn sort_for(&self, dir: &Path) -> Sort {
+        self.sort_memory
+            .get(dir)
+            .filter(|_| self.sort_per_directory)
+            .copied()
+            .unwrap_or(self.sort)
+    }
+
+    pub fn start_sort(&mut self) {
+        self.mode = Mode::Sort;
+        self.message = Some(format!(
+            "Sort by (currently {}):",
+            self.sort_for(&self.current_dir).label()
+        ));
+    }
+
+    pub fn cancel_sort(&mut self) {
+        self.mode = Mode::Normal;
         self.message = None;
     }
 
-    pub fn start_rename(&mut self) {
-        let entry_info = self
-            .selected_entry()
-            .map(|e| (e.name.clone(), e.name == ".."));
-
-        if let Some((name, is_parent)) = entry_info {
-            if is_parent {
-                self.message = Some("Cannot rename '..'".to_string());
+    /// Applies the sort change bound to `key`.
+    pub fn apply_sort_key(&mut self, key: char) {
+        self.mode = Mode::Normal;
+        let mut sort = self.sort_for(&self.current_dir);
+        match key {
+            'n' => sort.key = SortKey::Name,
+            'N' => sort.key = SortKey::Natural,
+            's' => sort.key = SortKey::Size,
+            'm' => sort.key = SortKey::Modified,
+            'e' => sort.key = SortKey::Extension,
+            'g' => sort.key = SortKey::Git,
+            'r' => sort.reverse = !sort.reverse,
+            'd' => sort.dirs_first = !sort.dirs_first,
+            'p' => {
+                self.sort_per_directory = !self.sort_per_directory;
+                if self.sort_per_directory {
+                    self.sort_memory.insert(self.current_dir.clone(), sort);
+                }
+                self.resort();
+                self.message = Some(if self.sort_per_directory {
+                    "Sort is remembered per directory".to_string()
+                } else {
+                    "Sort applies to every directory".to_string()
+                });
+                return;
+            }
+            _ => {
+                self.message = Some(format!("No sort option on '{}'", key));
                 return;
             }
-            self.mode = Mode::Rename;
-            self.input = name.chars().collect();
-            self.cursor = self.input.len();
-            self.message = None;
         }
-    }
 
-    pub fn toggle_help(&mut self) {
-        if self.mode == Mode::Help {
-            self.mode = Mode::Normal;
+        if self.sort_per_directory {
+            self.sort_memory.insert(self.current_dir.clone(), sort);
         } else {
-            self.mode = Mode::Help;
+            self.sort = sort;
         }
+        self.resort();
+        self.message = Some(format!("Sorted by {}", sort.label()));
+    }
+
+    /// Puts the listing in the current order, keeping the cursor on the
+    /// same entry.
+    fn resort(&mut self) {
+        let entries: Vec<Entry> = self
+            .all_entries
+            .iter()
+            .filter(|e| e.name != "..")
+            .cloned()
+            .collect();
+        self.install_entries(&entries);
     }
 
     // =========================================================================
-    // Input/Cursor Handling
+    // Copy Options
     // =========================================================================
 
-    pub fn input_char(&mut self, c: char) {
-        self.input.insert(self.cursor, c);
-        self.cursor += 1;
+    pub fn open_copy_options(&mut self) {
+        self.mode = Mode::CopyOptions;
+        self.message = None;
     }
 
-    pub fn input_backspace(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
-            self.input.remove(self.cursor);
-        }
+    pub fn close_copy_options(&mut self) {
+        self.mode = Mode::Normal;
     }
 
-    pub fn input_delete(&mut self) {
-        if self.cursor < self.input.len() {
-            self.input.remove(self.cursor);
-        }
+    pub fn toggle_preserve_timestamps(&mut self) {
+        self.copy_policy.preserve_timestamps = !self.copy_policy.preserve_timestamps;
     }
 
-    pub fn cursor_left(&mut self) {
-        self.cursor = self.cursor.saturating_sub(1);
+    pub fn toggle_preserve_permissions(&mut self) {
+        self.copy_policy.preserve_permissions = !self.copy_policy.preserve_permissions;
     }
 
-    pub fn cursor_right(&mut self) {
-        if self.cursor < self.input.len() {
-            self.cursor += 1;
-        }
+    pub fn toggle_symlink_policy(&mut self) {
+        self.copy_policy.symlinks = match self.copy_policy.symlinks {
+            SymlinkPolicy::Link => SymlinkPolicy::Follow,
+            SymlinkPolicy::Follow => SymlinkPolicy::Link,
+        };
     }
 
-    pub fn cursor_home(&mut self) {
-        self.cursor = 0;
-    }
+    // =========================================================================
+    // Trash
+    // =========================================================================
 
-    pub fn cursor_end(&mut self) {
-        self.cursor = self.input.len();
+    pub fn open_trash(&mut self) {
+        self.mode = Mode::Trash;
+        self.reload_trash();
+        if self.trash_items.is_empty() {
+            self.message = Some("Trash is empty".to_string());
+        }
     }
 
-    pub fn input_clear(&mut self) {
-        self.input.clear();
-        self.cursor = 0;
+    pub fn close_trash(&mut self) {
+        self.mode = Mode::Normal;
+        self.trash_items.clear();
+        self.message = None;
     }
 
-    pub fn confirm_rename(&mut self) {
-        if let Some(entry) = self.selected_entry() {
-            let new_name: String = self.input.iter().collect();
-            let old_path = self.current_dir.join(&entry.name);
-            let new_path = self.current_dir.join(&new_name);
+    fn reload_trash(&mut self) {
+        self.trash_items = trash::list_items();
+        let selected = self.trash_state.selected().unwrap_or(0);
+        self.trash_state.select(if self.trash_items.is_empty() {
+            None
+        } else {
+            Some(selected.min(self.trash_items.len() - 1))
+        });
+    }
 
-            if let Err(msg) = validate_filename(&new_name) {
-                self.message = Some(msg);
-                return;
-            }
+    pub fn selected_trash_item(&self) -> Option<&TrashItem> {
+        self.trash_state
+            .selected()
+            .and_then(|i| self.trash_items.get(i))
+    }
 
-            if old_path == new_path {
-                self.mode = Mode::Normal;
-                self.input.clear();
-                self.message = None;
-                return;
-            }
+    pub fn trash_move_up(&mut self) {
+        if let Some(selected) = self.trash_state.selected() {
+            self.trash_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
 
-            match fs::rename(&old_path, &new_path) {
-                Ok(_) => {
-                    self.message = Some(format!("Renamed to: {}", new_name));
-                    self.mode = Mode::Normal;
-                    self.input.clear();
-                    self.invalidate_git_cache();
-                    let _ = self.refresh();
-                    self.update_preview();
-                }
-                Err(e) => {
-                    self.message = Some(format!("Rename failed: {}", e));
-                }
+    pub fn trash_move_down(&mut self) {
+        if let Some(selected) = self.trash_state.selected() {
+            if selected + 1 < self.trash_items.len() {
+                self.trash_state.select(Some(selected + 1));
             }
         }
     }
 
-    pub fn start_delete(&mut self) {
-        let entry_info = self
-            .selected_entry()
-            .map(|e| (e.name.clone(), e.name == ".."));
-
-        if let Some((name, is_parent)) = entry_info {
-            if is_parent {
-                self.message = Some("Cannot delete '..'".to_string());
-                return;
+    /// Restores the selected trash item to its original location.
+    pub fn restore_trash_item(&mut self) {
+        let Some(item) = self.selected_trash_item().cloned() else {
+            return;
+        };
+        match trash::restore(&item) {
+            Ok(_) => {
+                self.message = Some(format!(
+                    "Restored: {}",
+                    item.original_path.to_string_lossy()
+                ));
+                self.invalidate_git_cache();
+                let _ = self.refresh();
+                self.update_preview();
             }
-            self.mode = Mode::ConfirmDelete;
-            self.message = Some(format!("Delete '{}'? (y/n)", name));
+            Err(e) => self.message = Some(format!("Restore failed: {}", e)),
         }
+        self.reload_trash();
     }
 
-    pub fn confirm_delete(&mut self) {
-        if let Some(path) = self.selected_path() {
-            let is_dir = path.is_dir();
-            let result = if is_dir {
-                fs::remove_dir_all(&path)
-            } else {
-                fs::remove_file(&path)
-            };
+    pub fn start_purge(&mut self) {
+        if let Some(item) = self.selected_trash_item() {
+            self.message = Some(format!(
+                "PERMANENTLY delete '{}' from trash? This cannot be undone; type Y to confirm",
+                item.name()
+            ));
+            self.mode = Mode::ConfirmPurge;
+        }
+    }
 
-            match result {
-                Ok(_) => {
-                    self.message = Some("Deleted successfully".to_string());
-                    self.invalidate_git_cache();
-                    let _ = self.refresh();
-                    // Adjust selection if needed
-                    if let Some(selected) = self.state.selected() {
-                        if selected >= self.filtered_indices.len() && selected > 0 {
-                            self.state.select(Some(selected - 1));
-                        }
-                    }
-                    self.update_preview();
-                }
-                Err(e) => {
-                    self.message = Some(format!("Delete failed: {}", e));
-                }
-            }
+    pub fn confirm_purge(&mut self) {
+        if let Some(item) = self.selected_trash_item().cloned() {
+            self.message = Some(match trash::purge(&item) {
+                Ok(_) => format!("Deleted: {}", item.name()),
+                Err(e) => format!("Delete failed: {}", e),
+            });
         }
-        self.mode = Mode::Normal;
+        self.mode = Mode::Trash;
+        self.reload_trash();
     }
 
-    pub fn cancel_delete(&mut self) {
-        self.mode = Mode::Normal;
+    pub fn cancel_purge(&mut self) {
+        self.mode = Mode::Trash;
         self.message = None;
     }
 }
@@ -1040,7 +3123,7 @@ impl App {
 // Helper Functions
 // =============================================================================
 
-fn is_text(data: &[u8]) -> bool {
+pub fn is_text(data: &[u8]) -> bool {
     if data.is_empty() {
         return true;
     }
@@ -1077,68 +3160,128 @@ fn is_hidden_file(name: &str, _path: &Path) -> bool {
     name.starts_with('.') && name != ".."
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> io::Result<()> {
-    use std::collections::HashSet;
-
-    fn copy_dir_inner(
-        src: &Path,
-        dst: &Path,
-        visited: &mut HashSet<PathBuf>,
-    ) -> io::Result<()> {
-        // Canonicalize to detect cycles (resolves symlinks)
-        let canonical_src = src.canonicalize().unwrap_or_else(|_| src.to_path_buf());
-
-        if !visited.insert(canonical_src.clone()) {
-            return Err(io::Error::new(
-                ErrorKind::Other,
-                format!("Symlink cycle detected: {}", src.display()),
-            ));
+/// Reads the entries of `dir` with their metadata, unsorted and without
+/// "..". Git statuses are filled in when the entries are shown.
+pub fn read_entries(dir: &Path) -> io::Result<Vec<Entry>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| {
+            let metadata = e.metadata().ok();
+            let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+            let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let readonly = metadata
+                .as_ref()
+                .map(|m| m.permissions().readonly())
+                .unwrap_or(false);
+            let name = e.file_name().to_string_lossy().to_string();
+            let is_hidden = is_hidden_file(&name, &e.path());
+            let name_lower = name.to_lowercase();
+            Entry {
+                name,
+                name_lower,
+                is_dir,
+                size,
+                modified,
+                is_hidden,
+                readonly,
+                git_status: None,
+            }
+        })
+        .collect())
+}
+
+/// The `(is_dir, name)` pairs the parent column shows for `entries`.
+fn parent_column_entries(entries: &[Entry], show_hidden: bool) -> Vec<(bool, String)> {
+    let mut items: Vec<&Entry> = entries
+        .iter()
+        .filter(|e| show_hidden || !e.is_hidden)
+        .collect();
+    items.sort_by(|a, b| compare_entries_by_dir_and_name(a, b, |e| e.is_dir, |e| &e.name_lower));
+    items.into_iter().map(|e| (e.is_dir, e.name.clone())).collect()
+}
+
+/// Reads the `(is_dir, name)` pairs of `path`, sorted like the file list.
+/// Fails with `Interrupted` once `cancelled` returns true.
+pub fn list_directory_until(
+    path: &Path,
+    show_hidden: bool,
+    cancelled: &dyn Fn() -> bool,
+) -> io::Result<Vec<(bool, String)>> {
+    // (is_dir, name, name_lower) - pre-compute lowercase for sorting
+    let mut items: Vec<(bool, String, String)> = Vec::new();
+    for entry in fs::read_dir(path)? {
+        if cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !show_hidden && is_hidden_file(&name, &entry.path()) {
+            continue;
         }
+        let is_dir = entry.metadata().map(|m| m.is_dir()).unwrap_or(false);
+        let name_lower = name.to_lowercase();
+        items.push((is_dir, name, name_lower));
+    }
 
-        fs::create_dir(dst)?;
-
-        for entry in fs::read_dir(src)? {
-            let entry = entry?;
-            let src_path = entry.path();
-            let dst_path = dst.join(entry.file_name());
-            let metadata = fs::symlink_metadata(&src_path)?;
-
-            if metadata.file_type().is_symlink() {
-                let target_meta = fs::metadata(&src_path)?;
-                if target_meta.is_dir() {
-                    return Err(io::Error::new(
-                        ErrorKind::Other,
-                        format!(
-                            "Refusing to copy symlinked directory: {}",
-                            src_path.display()
-                        ),
-                    ));
-                }
+    // Sort: directories first, then alphabetically by lowercase name
+    items.sort_by(|a, b| compare_entries_by_dir_and_name(a, b, |e| e.0, |e| &e.2));
 
-                fs::copy(&src_path, &dst_path)?;
-                continue;
-            }
+    Ok(items
+        .into_iter()
+        .map(|(is_dir, name, _)| (is_dir, name))
+        .collect())
+}
 
-            if metadata.is_dir() {
-                copy_dir_inner(&src_path, &dst_path, visited)?;
-            } else {
-                fs::copy(&src_path, &dst_path)?;
-            }
+/// Returns the file name of a path for display in messages.
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Builds the status message for a finished transfer job.
+fn summarize_transfer(outcome: &JobOutcome) -> String {
+    let action = match outcome.kind {
+        TransferKind::Copy => "Pasted",
+        TransferKind::Move => "Moved",
+    };
+    let done = outcome.completed.len();
+    let summary = if outcome.cancelled {
+        format!(
+            "{} {} of {} items before cancel",
+            action, done, outcome.total
+        )
+    } else {
+        match (outcome.completed.as_slice(), outcome.failed.as_slice()) {
+            ([(_, dest)], []) => format!("{}: {}", action, display_name(dest)),
+            (_, []) => format!("{} {} items", action, done),
+            ([], [(_, err)]) => format!("Paste failed: {}", err),
+            (_, [(src, err), ..]) => format!(
+                "{} {} of {} items, {} failed ({}: {})",
+                action,
+                done,
+                outcome.total,
+                outcome.failed.len(),
+                display_name(src),
+                err
+            ),
         }
-        Ok(())
+    };
+    match outcome.warnings.first() {
+        Some((src, warning)) => format!("{}; {}: {}", summary, display_name(src), warning),
+        None => summary,
     }
-
-    let mut visited = HashSet::new();
-    copy_dir_inner(src, dst, &mut visited)
 }
 
 /// Remove Windows UNC prefix (\\?\) if present
 fn normalize_path(path: &Path) -> PathBuf {
     let path_str = path.to_string_lossy();
-    if path_str.starts_with(r"\\?\") {
-        PathBuf::from(&path_str[4..])
-    } else {
-        path.to_path_buf()
+    match path_str.strip_prefix(r"\\?\") {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path.to_path_buf(),
     }
 }
 
@@ -1264,7 +3407,7 @@ fn get_git_status(dir: &Path) -> HashMap<String, GitStatus> {
     statuses
 }
 
-fn git_status_priority(status: GitStatus) -> u8 {
+pub fn git_status_priority(status: GitStatus) -> u8 {
     match status {
         GitStatus::Conflict => 5,
         GitStatus::Modified => 4,
@@ -1274,7 +3417,7 @@ fn git_status_priority(status: GitStatus) -> u8 {
     }
 }
 
-fn parse_png_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
+pub fn parse_png_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
     if header.len() >= 24 && &header[0..8] == b"\x89PNG\r\n\x1a\n" {
         let width = u32::from_be_bytes([header[16], header[17], header[18], header[19]]);
         let height = u32::from_be_bytes([header[20], header[21], header[22], header[23]]);
@@ -1284,7 +3427,7 @@ fn parse_png_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
     }
 }
 
-fn parse_jpeg_dimensions(data: &[u8]) -> (u32, u32, &'static str) {
+pub fn parse_jpeg_dimensions(data: &[u8]) -> (u32, u32, &'static str) {
     if data.len() < 2 || data[0] != 0xFF || data[1] != 0xD8 {
         return (0, 0, "JPEG");
     }
@@ -1311,7 +3454,7 @@ fn parse_jpeg_dimensions(data: &[u8]) -> (u32, u32, &'static str) {
     (0, 0, "JPEG")
 }
 
-fn parse_gif_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
+pub fn parse_gif_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
     if header.len() >= 10 && (&header[0..3] == b"GIF") {
         let width = u16::from_le_bytes([header[6], header[7]]) as u32;
         let height = u16::from_le_bytes([header[8], header[9]]) as u32;
@@ -1321,7 +3464,7 @@ fn parse_gif_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
     }
 }
 
-fn parse_bmp_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
+pub fn parse_bmp_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
     if header.len() >= 26 && &header[0..2] == b"BM" {
         let width = u32::from_le_bytes([header[18], header[19], header[20], header[21]]);
         let height = u32::from_le_bytes([header[22], header[23], header[24], header[25]]);
@@ -1338,6 +3481,16 @@ fn parse_bmp_dimensions(header: &[u8]) -> (u32, u32, &'static str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::temp_dir;
+    use std::sync::mpsc::{self, Receiver};
+
+    /// An app on `dir` that neither reads nor saves the user's settings,
+    /// bookmarks and jump database, and the channel its workers post to.
+    fn test_app(dir: &Path) -> (App, Receiver<Message>) {
+        let (sender, receiver) = mpsc::channel();
+        let app = App::with_dirs(dir.to_path_buf(), sender, None, None).unwrap();
+        (app, receiver)
+    }
 
     #[test]
     fn test_is_text_empty() {
@@ -1475,7 +3628,7 @@ mod tests {
 
     #[test]
     fn test_entry_sorting() {
-        let mut entries = vec![
+        let mut entries = [
             Entry {
                 name: "zebra.txt".into(),
                 name_lower: "zebra.txt".into(),
@@ -1516,4 +3669,519 @@ mod tests {
         assert_eq!(entries[1].name, "beta.txt");
         assert_eq!(entries[2].name, "zebra.txt");
     }
+
+    #[test]
+    fn test_cursor_restored_by_name() {
+        let dir = temp_dir("app-cursor");
+        for name in ["a", "b", "c"] {
+            fs::create_dir_all(dir.join(name).join("inner")).unwrap();
+        }
+        fs::write(dir.join("b").join("z.txt"), b"").unwrap();
+
+        let (mut app, _messages) = test_app(&dir);
+        app.select_entry_named("b");
+        app.enter_selected().unwrap();
+        app.select_entry_named("z.txt");
+
+        // Going up selects the directory just left
+        app.go_to_parent();
+        assert_eq!(app.selected_entry().unwrap().name, "b");
+
+        // Coming back restores the entry selected on the last visit
+        app.enter_selected().unwrap();
+        assert_eq!(app.selected_entry().unwrap().name, "z.txt");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tabs_keep_their_own_state() {
+        let dir = temp_dir("app-tabs");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("one.txt"), b"").unwrap();
+        fs::write(dir.join("two.txt"), b"").unwrap();
+
+        let (mut app, _messages) = test_app(&dir);
+        app.select_entry_named("one.txt");
+        app.copy_file();
+        app.toggle_selection();
+
+        app.new_tab();
+        assert_eq!(app.active_tab, 1);
+        assert!(app.selection.is_empty());
+        app.select_entry_named("sub");
+        app.enter_selected().unwrap();
+        app.filter = "x".to_string();
+
+        app.switch_tab(0);
+        let canonical = dir.canonicalize().unwrap();
+        assert_eq!(app.current_dir, canonical);
+        assert_eq!(app.selection.len(), 1);
+        assert!(app.filter.is_empty());
+        assert_eq!(app.tab_titles()[1], "sub");
+
+        // The clipboard is shared between tabs
+        app.next_tab();
+        assert_eq!(app.current_dir, canonical.join("sub"));
+        assert_eq!(app.filter, "x");
+        assert!(app.clipboard.is_some());
+
+        app.close_tab();
+        assert_eq!(app.tab_titles().len(), 1);
+        assert_eq!(app.current_dir, canonical);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dual_pane_copies_to_other_panel() {
+        let dir = temp_dir("app-dual");
+        fs::create_dir_all(dir.join("dest")).unwrap();
+        fs::write(dir.join("file.txt"), b"data").unwrap();
+
+        let (mut app, receiver) = test_app(&dir);
+        app.toggle_dual_pane();
+        app.switch_pane();
+        assert!(app.focus_right);
+        app.select_entry_named("dest");
+        app.enter_selected().unwrap();
+        app.switch_pane();
+
+        app.select_entry_named("file.txt");
+        app.transfer_to_other_pane(TransferKind::Copy);
+        loop {
+            let message = receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .expect("transfer did not finish");
+            let finished = matches!(message, Message::TransferFinished(_));
+            app.handle_message(message);
+            if finished {
+                break;
+            }
+        }
+        assert_eq!(fs::read(dir.join("dest/file.txt")).unwrap(), b"data");
+        assert!(app.clipboard.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_undo_moves_on_the_transfer_worker() {
+        let dir = temp_dir("app-undo");
+        fs::create_dir_all(dir.join("dest")).unwrap();
+        fs::write(dir.join("dest/file.txt"), b"data").unwrap();
+
+        let (mut app, receiver) = test_app(&dir);
+        let moved = (dir.join("file.txt"), dir.join("dest/file.txt"));
+        app.journal.record(Operation::Move(vec![moved]));
+        app.confirm_undo();
+        assert!(app.journal.is_busy());
+        assert_eq!(app.message.as_deref(), Some("Undo running in the background"));
+
+        while app.journal.is_busy() {
+            let message = receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .expect("undo did not finish");
+            app.handle_message(message);
+        }
+        assert_eq!(fs::read(dir.join("file.txt")).unwrap(), b"data");
+        assert!(app.message.unwrap().starts_with("Undo: move 'file.txt' back"));
+        assert!(app.journal.peek_redo().is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parent_column_is_cached() {
+        let dir = temp_dir("app-miller");
+        fs::create_dir_all(dir.join("cur")).unwrap();
+        fs::write(dir.join("cur/a.txt"), b"").unwrap();
+        fs::write(dir.join("cur/b.txt"), b"").unwrap();
+
+        let (mut app, _messages) = test_app(&dir.join("cur"));
+        app.toggle_miller_columns();
+        let column = app.parent_column.as_ref().unwrap();
+        assert_eq!(column.dir, dir);
+        assert_eq!(column.entries, vec![(true, "cur".to_string())]);
+
+        // Moving within the directory keeps the cached listing
+        fs::write(dir.join("new.txt"), b"").unwrap();
+        app.move_down();
+        app.refresh().unwrap();
+        assert_eq!(app.parent_column.as_ref().unwrap().entries.len(), 1);
+
+        // Changing the hidden-file setting re-reads it
+        app.toggle_hidden();
+        assert_eq!(app.parent_column.as_ref().unwrap().entries.len(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_modes() {
+        let dir = temp_dir("app-filter");
+        for name in ["cargo.lock", "main.rs", "mod_a.rs", "README.md"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        let names = |app: &App| app.entries().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        let (mut app, _messages) = test_app(&dir);
+        app.filter_mode = FilterMode::Fuzzy;
+        app.filter = "mar".to_string();
+        app.apply_filter();
+        // Ordered by score rather than by name, ".." staying first
+        assert_eq!(names(&app), vec!["..", "mod_a.rs", "main.rs"]);
+        let index = app.filtered_indices[2];
+        assert_eq!(app.filter_positions[&index], vec![0, 1, 5]);
+
+        app.filter_mode = FilterMode::Glob;
+        app.filter = "*.rs".to_string();
+        app.apply_filter();
+        assert_eq!(names(&app), vec!["..", "main.rs", "mod_a.rs"]);
+
+        // An invalid regex shows every entry and reports the problem
+        app.filter_mode = FilterMode::Regex;
+        app.filter = "(".to_string();
+        app.apply_filter();
+        assert_eq!(app.entries().count(), 5);
+        assert!(app.message.as_deref().unwrap().starts_with("Invalid pattern"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sort_per_directory() {
+        let dir = temp_dir("app-sort");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("file10"), b"1").unwrap();
+        fs::write(dir.join("file2"), b"22").unwrap();
+        let names = |app: &App| app.entries().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        let (mut app, _messages) = test_app(&dir);
+        app.sort = Sort::default();
+        app.sort_per_directory = false;
+        app.select_entry_named("file2");
+        app.apply_sort_key('N');
+        assert_eq!(names(&app), vec!["..", "sub", "file2", "file10"]);
+        assert_eq!(app.selected_entry().unwrap().name, "file2");
+
+        app.apply_sort_key('p');
+        app.apply_sort_key('s');
+        assert_eq!(names(&app), vec!["..", "sub", "file2", "file10"]);
+        assert_eq!(app.sort.key, SortKey::Natural);
+
+        // Other directories keep the shared sort
+        app.select_entry_named("sub");
+        app.enter_selected().unwrap();
+        assert_eq!(app.sort_for(&app.current_dir).key, SortKey::Natural);
+        app.go_to_parent();
+        assert_eq!(app.sort_for(&app.current_dir).key, SortKey::Size);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_attribute_filters_persist() {
+        let dir = temp_dir("app-attrs");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("big.rs"), vec![b'x'; 2048]).unwrap();
+        fs::write(dir.join("small.rs"), b"x").unwrap();
+        fs::write(dir.join("sub/other.rs"), vec![b'x'; 4096]).unwrap();
+        let names = |app: &App| app.entries().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        let (mut app, _messages) = test_app(&dir);
+        app.filter_mode = FilterMode::Substring;
+        app.start_search();
+        for c in "size>1k big".chars() {
+            app.update_search(c);
+        }
+        assert_eq!(names(&app), vec!["..", "big.rs"]);
+        app.confirm_search();
+        assert_eq!(app.filter, "big");
+        assert_eq!(app.attribute_filters.len(), 1);
+
+        // Directories pass size conditions; the condition outlives the
+        // name filter when navigating
+        app.clear_filter();
+        assert_eq!(names(&app), vec!["..", "sub", "big.rs"]);
+        app.select_entry_named("sub");
+        app.enter_selected().unwrap();
+        assert_eq!(names(&app), vec!["..", "other.rs"]);
+        app.go_to_parent();
+        assert_eq!(names(&app), vec!["..", "sub", "big.rs"]);
+
+        app.clear_attribute_filters();
+        assert_eq!(app.entries().count(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Handles worker messages until one `wanted` returns something for,
+    /// which is left unhandled.
+    fn receive<T>(
+        app: &mut App,
+        receiver: &Receiver<Message>,
+        wanted: impl Fn(&Message) -> Option<T>,
+    ) -> T {
+        loop {
+            let message = receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .expect("no message from the workers");
+            if let Some(found) = wanted(&message) {
+                return found;
+            }
+            app.handle_message(message);
+        }
+    }
+
+    /// Shows the listings the workers are reading.
+    fn settle(app: &mut App, receiver: &Receiver<Message>) {
+        while !app.listing_requests.is_empty() {
+            let message = receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .expect("no listing from the workers");
+            app.handle_message(message);
+        }
+    }
+
+    #[test]
+    fn test_listing_from_worker() {
+        let dir = temp_dir("app-listing");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), b"").unwrap();
+        fs::write(dir.join("sub/inner.txt"), b"").unwrap();
+        let names = |app: &App| app.entries().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        let (mut app, receiver) = test_app(&dir);
+        app.start_workers();
+
+        // The new directory shows up once a worker has read it
+        app.select_entry_named("sub");
+        app.enter_selected().unwrap();
+        assert_eq!(app.entries().count(), 0);
+        settle(&mut app, &receiver);
+        assert_eq!(names(&app), vec!["..", "inner.txt"]);
+
+        // Going up still selects the directory just left
+        app.go_to_parent();
+        settle(&mut app, &receiver);
+        assert_eq!(names(&app), vec!["..", "sub", "a.txt"]);
+        assert_eq!(app.selected_entry().unwrap().name, "sub");
+
+        // A listing overtaken by a newer read is dropped
+        app.refresh().unwrap();
+        let stale = app.listing_requests[&app.current_dir] - 1;
+        app.apply_listing(app.current_dir.clone(), stale, Ok(Vec::new()));
+        assert_eq!(app.entries().count(), 3);
+        settle(&mut app, &receiver);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_selects_result_from_worker() {
+        let dir = temp_dir("app-find");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in ["a.txt", "target.txt", "z.txt"] {
+            fs::write(dir.join("sub").join(name), b"").unwrap();
+        }
+
+        let (mut app, receiver) = test_app(&dir);
+        app.start_workers();
+        app.start_find();
+        while !app.finder.as_ref().unwrap().is_done() {
+            let message = receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .expect("finder did not finish");
+            app.handle_message(message);
+        }
+        app.input = "target".chars().collect();
+        app.update_find_results();
+        assert_eq!(app.find_results[0].path, "sub/target.txt");
+
+        // The result is selected once the worker has read its directory
+        app.confirm_find();
+        settle(&mut app, &receiver);
+        assert_eq!(app.current_dir, dir.canonicalize().unwrap().join("sub"));
+        assert_eq!(app.selected_entry().unwrap().name, "target.txt");
+        assert_eq!(app.message, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_delete_keeps_cursor_in_place_with_worker() {
+        let dir = temp_dir("app-delete");
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let (mut app, receiver) = test_app(&dir);
+        app.start_workers();
+        settle(&mut app, &receiver);
+
+        // The entry below the deleted one takes its place
+        app.select_entry_named("c.txt");
+        app.confirm_permanent_delete();
+        settle(&mut app, &receiver);
+        assert_eq!(app.selected_entry().unwrap().name, "d.txt");
+
+        // Past the end of the list the entry above is selected
+        app.confirm_permanent_delete();
+        settle(&mut app, &receiver);
+        assert_eq!(app.selected_entry().unwrap().name, "b.txt");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_status_from_worker() {
+        let dir = temp_dir("app-git");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("changed.txt"), b"").unwrap();
+
+        let (mut app, receiver) = test_app(&dir);
+        app.start_workers();
+        let requested = |app: &mut App| {
+            receive(app, &receiver, |message| match message {
+                Message::GitStatus {
+                    dir, generation, ..
+                } => Some((dir.clone(), *generation)),
+                _ => None,
+            })
+        };
+
+        // Entering a directory asks a worker instead of running git inline
+        app.select_entry_named("sub");
+        app.enter_selected().unwrap();
+        assert_eq!(requested(&mut app).0, app.current_dir);
+
+        // A result overtaken by a newer request for the same directory is
+        // dropped
+        app.go_to_parent();
+        let (parent, stale) = requested(&mut app);
+        app.invalidate_git_cache();
+        app.refresh().unwrap();
+        let (_, latest) = requested(&mut app);
+        settle(&mut app, &receiver);
+        let statuses = HashMap::from([("changed.txt".to_string(), GitStatus::Modified)]);
+        let status = |app: &App| {
+            let entry = app.entries().find(|e| e.name == "changed.txt");
+            entry.unwrap().git_status
+        };
+        app.apply_git_status(parent.clone(), stale, statuses.clone());
+        assert_eq!(status(&app), None);
+        app.apply_git_status(parent, latest, statuses);
+        assert_eq!(status(&app), Some(GitStatus::Modified));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_grep_match_previewed_from_worker() {
+        let dir = temp_dir("app-grep");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let text: Vec<String> = (1..=10)
+            .map(|i| if i == 6 { "the needle".to_string() } else { format!("line {}", i) })
+            .collect();
+        fs::write(dir.join("sub/notes.txt"), text.join("\n")).unwrap();
+        fs::write(dir.join("sub/a.txt"), b"").unwrap();
+
+        let (mut app, receiver) = test_app(&dir);
+        app.start_workers();
+        let next = |app: &mut App| {
+            let message = receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .expect("no message from the workers");
+            app.handle_message(message);
+        };
+        app.start_grep();
+        app.input = "needle".chars().collect();
+        app.confirm_grep();
+        while !app.grep.as_ref().unwrap().is_done() {
+            next(&mut app);
+        }
+
+        // The match preview is loaded once the listing of its directory has
+        // arrived and the file is selected
+        app.confirm_grep();
+        while !matches!(app.preview, Preview::Text { .. }) {
+            next(&mut app);
+        }
+        assert_eq!(app.current_dir, dir.canonicalize().unwrap().join("sub"));
+        assert_eq!(app.selected_entry().unwrap().name, "notes.txt");
+        let Preview::Text { content, .. } = &app.preview else {
+            unreachable!();
+        };
+        assert!(content.starts_with("line 3\nline 4\nline 5\nthe needle"));
+        assert_eq!(app.preview_match, Some((3, 4..10)));
+        assert_eq!(app.message.as_deref(), Some("notes.txt:6"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_preview_from_worker() {
+        let dir = temp_dir("app-preview");
+        fs::write(dir.join("a.txt"), b"first").unwrap();
+        fs::write(dir.join("b.txt"), b"second").unwrap();
+
+        let (mut app, receiver) = test_app(&dir);
+        app.start_workers();
+        let next_preview = || loop {
+            match receiver.recv() {
+                Ok(Message::Preview { key, preview }) => return (key, preview),
+                Ok(_) => {}
+                Err(_) => panic!("no preview loaded"),
+            }
+        };
+
+        // A worker loads the preview while a placeholder is shown
+        app.select_entry_named("a.txt");
+        app.update_preview();
+        assert!(matches!(app.preview, Preview::Loading));
+        let (key, preview) = next_preview();
+        app.apply_preview(key, preview);
+        assert!(matches!(&app.preview, Preview::Text { content, .. } if content == "first"));
+
+        // Results for a selection that moved on are dropped; revisiting a
+        // file is served from the cache
+        app.select_entry_named("b.txt");
+        app.update_preview();
+        app.select_entry_named("a.txt");
+        app.update_preview();
+        assert!(matches!(&app.preview, Preview::Text { content, .. } if content == "first"));
+        let stale = PreviewKey {
+            path: dir.canonicalize().unwrap().join("b.txt"),
+            is_dir: false,
+            modified: None,
+            size: 6,
+            show_hidden: false,
+            line: None,
+        };
+        app.apply_preview(stale, Preview::None);
+        assert!(matches!(&app.preview, Preview::Text { content, .. } if content == "first"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_refused_pattern_rename_keeps_preview() {
+        let dir = temp_dir("app-pattern");
+        fs::write(dir.join("a.txt"), b"").unwrap();
+
+        let (mut app, _messages) = test_app(&dir);
+        app.select_entry_named("a.txt");
+        app.start_pattern_rename();
+        app.input = "a/b".chars().collect();
+        app.update_pattern_preview();
+        // Another program takes the new name before the rename is confirmed
+        fs::write(dir.join("b.txt"), b"").unwrap();
+        app.confirm_pattern_rename();
+        assert_eq!(app.mode, Mode::PatternRename);
+        assert_eq!(app.pattern_preview, vec![("a.txt".to_string(), "b.txt".to_string())]);
+        assert!(dir.join("a.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,193 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Name of the bookmarks file inside the config directory
+const BOOKMARKS_FILE_NAME: &str = "bookmarks";
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Directories saved under a single-character key, persisted to
+/// `$XDG_CONFIG_HOME/fylins/bookmarks` as `<key> <path>` lines.
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    entries: BTreeMap<char, PathBuf>,
+    /// File the bookmarks are saved to; None keeps them in memory only.
+    file: Option<PathBuf>,
+}
+
+// =============================================================================
+// Bookmarks Implementation
+// =============================================================================
+
+impl Bookmarks {
+    /// Loads the bookmarks file in `dir` (usually the config directory). A
+    /// missing file yields no bookmarks; an unreadable one yields no
+    /// bookmarks plus a description of the problem. Without a directory the
+    /// bookmarks are kept in memory only.
+    pub fn load(dir: Option<&Path>) -> (Bookmarks, Option<String>) {
+        let file = dir.map(|dir| dir.join(BOOKMARKS_FILE_NAME));
+        let mut bookmarks = Bookmarks {
+            entries: BTreeMap::new(),
+            file: file.clone(),
+        };
+        let Some(file) = file else {
+            return (bookmarks, None);
+        };
+        match fs::read_to_string(&file) {
+            Ok(text) => {
+                bookmarks.entries = parse(&text);
+                (bookmarks, None)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (bookmarks, None),
+            Err(e) => (
+                bookmarks,
+                Some(format!("Cannot read bookmarks {}: {}", file.display(), e)),
+            ),
+        }
+    }
+
+    pub fn get(&self, key: char) -> Option<&PathBuf> {
+        self.entries.get(&key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, &PathBuf)> {
+        self.entries.iter().map(|(k, p)| (*k, p))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Saves `path` under `key`, replacing any previous bookmark. Paths
+    /// with line breaks cannot be stored in the one-per-line file.
+    pub fn set(&mut self, key: char, path: &Path) -> io::Result<()> {
+        if path.to_string_lossy().contains(['\n', '\r']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path contains a line break",
+            ));
+        }
+        let previous = self.entries.insert(key, path.to_path_buf());
+        let result = self.save();
+        if result.is_err() {
+            // Keep memory and file in sync
+            match previous {
+                Some(previous) => self.entries.insert(key, previous),
+                None => self.entries.remove(&key),
+            };
+        }
+        result
+    }
+
+    pub fn remove(&mut self, key: char) -> io::Result<Option<PathBuf>> {
+        let Some(removed) = self.entries.remove(&key) else {
+            return Ok(None);
+        };
+        match self.save() {
+            Ok(_) => Ok(Some(removed)),
+            Err(e) => {
+                self.entries.insert(key, removed);
+                Err(e)
+            }
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write then rename, so a crash never leaves a truncated file
+        let temp = file.with_extension("tmp");
+        fs::write(&temp, format(&self.entries))?;
+        fs::rename(&temp, file)
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Keys that can name a bookmark.
+pub fn is_valid_key(key: char) -> bool {
+    key.is_ascii_alphanumeric()
+}
+
+fn parse(text: &str) -> BTreeMap<char, PathBuf> {
+    text.lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let key = chars.next().filter(|k| is_valid_key(*k))?;
+            let path = chars.as_str().strip_prefix(' ')?;
+            (!path.is_empty()).then(|| (key, PathBuf::from(path)))
+        })
+        .collect()
+}
+
+fn format(entries: &BTreeMap<char, PathBuf>) -> String {
+    entries
+        .iter()
+        .map(|(key, path)| format!("{} {}\n", key, path.display()))
+        .collect()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_parse_and_format_roundtrip() {
+        let text = "a /home/me/work project\nb /tmp\n# not a bookmark\n\nz\n";
+        let entries = parse(text);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[&'a'], PathBuf::from("/home/me/work project"));
+        assert_eq!(format(&entries), "a /home/me/work project\nb /tmp\n");
+    }
+
+    #[test]
+    fn test_set_and_remove_persist() {
+        let dir = temp_dir("bookmarks");
+        let file = dir.join("bookmarks");
+        let mut bookmarks = Bookmarks {
+            entries: BTreeMap::new(),
+            file: Some(file.clone()),
+        };
+
+        bookmarks.set('w', Path::new("/work")).unwrap();
+        bookmarks.set('t', Path::new("/tmp")).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "t /tmp\nw /work\n");
+
+        assert_eq!(bookmarks.remove('w').unwrap(), Some(PathBuf::from("/work")));
+        assert_eq!(bookmarks.remove('w').unwrap(), None);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "t /tmp\n");
+
+        // A line break would split the bookmark into two broken lines
+        let err = bookmarks.set('n', Path::new("/tmp/a\nb /etc")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(bookmarks.get('n'), None);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "t /tmp\n");
+        assert!(!file.with_extension("tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    env, fs,
+    path::{is_separator, Path, PathBuf},
+};
+
+// =============================================================================
+// Path Expansion
+// =============================================================================
+
+/// Expands a leading `~` and `$VAR` / `${VAR}` references, then resolves
+/// the result against `base` if it is relative.
+pub fn expand_path(input: &str, base: &Path) -> Result<PathBuf, String> {
+    expand_with(input, base, |name| env::var(name).ok())
+}
+
+fn expand_with(
+    input: &str,
+    base: &Path,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<PathBuf, String> {
+    let mut expanded = String::with_capacity(input.len());
+    let mut rest = input;
+
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(is_separator) {
+            let home = dirs_next::home_dir().ok_or("Cannot find the home directory")?;
+            expanded.push_str(&home.to_string_lossy());
+            rest = after;
+        }
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remainder) = match after.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}').ok_or("Unclosed '${' in path")?;
+                (&braced[..end], &braced[end + 1..])
+            }
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            // A lone '$' is just a character
+            expanded.push('$');
+        } else {
+            let value = lookup(name).ok_or_else(|| format!("${} is not set", name))?;
+            expanded.push_str(&value);
+        }
+        rest = remainder;
+    }
+    expanded.push_str(rest);
+
+    Ok(base.join(expanded))
+}
+
+/// The deepest ancestor of `path` (possibly `path` itself) that exists.
+pub fn closest_existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| !p.as_os_str().is_empty() && p.exists())
+}
+
+// =============================================================================
+// Completion
+// =============================================================================
+
+/// Splits typed input into the directory part (up to and including the last
+/// separator) and the partial name after it.
+pub fn split_input(input: &str) -> (&str, &str) {
+    match input.rfind(is_separator) {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    }
+}
+
+/// Names of the child directories that complete the partial name in `input`,
+/// sorted. Hidden directories are offered only once the name starts with '.'.
+pub fn directory_completions(input: &str, base: &Path) -> Vec<String> {
+    let (dir_part, partial) = split_input(input);
+    let Ok(dir) = expand_path(dir_part, base) else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let partial_lower = partial.to_lowercase();
+    let mut names: Vec<String> = read_dir
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.') || partial.starts_with('.'))
+        .filter(|name| name.to_lowercase().starts_with(&partial_lower))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+/// Longest prefix shared by all `names`, compared case-insensitively and
+/// taken from the first name.
+pub fn common_prefix(names: &[String]) -> String {
+    let Some(first) = names.first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for name in &names[1..] {
+        len = first
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a.to_lowercase().eq(b.to_lowercase()))
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+            .min(len);
+    }
+    first[..len].to_string()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "PROJ").then(|| "/work/project".to_string())
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let base = Path::new("/base");
+        assert_eq!(
+            expand_with("$PROJ/api", base, lookup).unwrap(),
+            PathBuf::from("/work/project/api")
+        );
+        assert_eq!(
+            expand_with("${PROJ}s", base, lookup).unwrap(),
+            PathBuf::from("/work/projects")
+        );
+        assert_eq!(
+            expand_with("docs/$", base, lookup).unwrap(),
+            PathBuf::from("/base/docs/$")
+        );
+        assert_eq!(
+            expand_with("$NOPE/x", base, lookup).unwrap_err(),
+            "$NOPE is not set"
+        );
+        assert!(expand_with("${PROJ", base, lookup).is_err());
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs_next::home_dir().unwrap();
+        let base = Path::new("/base");
+        assert_eq!(expand_with("~", base, lookup).unwrap(), home);
+        assert_eq!(expand_with("~/src", base, lookup).unwrap(), home.join("src"));
+        // Only a leading '~' on its own means home
+        assert_eq!(
+            expand_with("~user", base, lookup).unwrap(),
+            PathBuf::from("/base/~user")
+        );
+    }
+
+    #[test]
+    fn test_completions_and_prefix() {
+        let dir = temp_dir("complete");
+        for sub in ["Projects", "proto", "public", ".profile"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        fs::write(dir.join("program.txt"), "").unwrap();
+
+        let names = directory_completions("pro", &dir);
+        assert_eq!(names, vec!["Projects", "proto"]);
+        assert_eq!(common_prefix(&names), "Pro");
+        assert_eq!(directory_completions(".pro", &dir), vec![".profile"]);
+
+        let input = format!("{}/pu", dir.display());
+        assert_eq!(directory_completions(&input, Path::new("/")), vec!["public"]);
+
+        let missing = dir.join("proto/a/b");
+        assert_eq!(closest_existing_ancestor(&missing), Some(dir.join("proto").as_path()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
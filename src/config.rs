@@ -0,0 +1,172 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::filter::FilterMode;
+use crate::sort::{Sort, SortKey};
+use crate::transfer::{CopyPolicy, SymlinkPolicy};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Name of the directory holding fylins files below the user config dir
+const CONFIG_DIR_NAME: &str = "fylins";
+
+/// Name of the settings file inside the config directory
+const CONFIG_FILE_NAME: &str = "config";
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// User settings read from `$XDG_CONFIG_HOME/fylins/config`.
+///
+/// The file holds one `key = value` pair per line; `#` starts a comment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub copy_policy: CopyPolicy,
+    /// Match mode the `/` filter starts in
+    pub filter_mode: FilterMode,
+    pub sort: Sort,
+    /// Remember sort changes for each directory separately
+    pub sort_per_directory: bool,
+}
+
+// =============================================================================
+// Config Implementation
+// =============================================================================
+
+impl Config {
+    /// Loads the config file in `dir` (usually `config_dir()`). A missing
+    /// file or directory yields the defaults; a broken file yields the
+    /// defaults plus a description of the first problem.
+    pub fn load(dir: Option<&Path>) -> (Config, Option<String>) {
+        let Some(path) = dir.map(|dir| dir.join(CONFIG_FILE_NAME)) else {
+            return (Config::default(), None);
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => match Config::parse(&text) {
+                Ok(config) => (config, None),
+                Err(e) => (
+                    Config::default(),
+                    Some(format!("Config {}: {}", path.display(), e)),
+                ),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (Config::default(), None),
+            Err(e) => (
+                Config::default(),
+                Some(format!("Cannot read config {}: {}", path.display(), e)),
+            ),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected 'key = value'", number + 1))?;
+            config
+                .set(key.trim(), value.trim())
+                .map_err(|e| format!("line {}: {}", number + 1, e))?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "preserve_timestamps" => self.copy_policy.preserve_timestamps = parse_bool(value)?,
+            "preserve_permissions" => self.copy_policy.preserve_permissions = parse_bool(value)?,
+            "symlinks" => {
+                self.copy_policy.symlinks = match value {
+                    "link" => SymlinkPolicy::Link,
+                    "follow" => SymlinkPolicy::Follow,
+                    _ => return Err(format!("symlinks must be 'link' or 'follow', got '{}'", value)),
+                }
+            }
+            "filter_mode" => {
+                self.filter_mode = FilterMode::parse(value).ok_or_else(|| {
+                    format!("filter_mode must be substring, fuzzy, glob or regex, got '{}'", value)
+                })?
+            }
+            "sort" => {
+                self.sort.key = SortKey::parse(value).ok_or_else(|| {
+                    format!(
+                        "sort must be name, natural, size, modified, extension or git, got '{}'",
+                        value
+                    )
+                })?
+            }
+            "sort_reverse" => self.sort.reverse = parse_bool(value)?,
+            "dirs_first" => self.sort.dirs_first = parse_bool(value)?,
+            "sort_per_directory" => self.sort_per_directory = parse_bool(value)?,
+            _ => return Err(format!("unknown setting '{}'", key)),
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Directory for fylins config and state files.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs_next::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME))
+}
+
+/// Directory for data fylins collects by itself, such as the jump database.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs_next::data_dir().map(|dir| dir.join(CONFIG_DIR_NAME))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "yes" | "on" => Ok(true),
+        "false" | "no" | "off" => Ok(false),
+        _ => Err(format!("expected true or false, got '{}'", value)),
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_copy_policy() {
+        let config = Config::parse(
+            "# copy settings\npreserve_timestamps = false\n\nsymlinks = follow # deref\n",
+        )
+        .unwrap();
+        assert!(!config.copy_policy.preserve_timestamps);
+        assert!(config.copy_policy.preserve_permissions);
+        assert_eq!(config.copy_policy.symlinks, SymlinkPolicy::Follow);
+        assert_eq!(config.filter_mode, FilterMode::Substring);
+        let config = Config::parse("filter_mode = fuzzy").unwrap();
+        assert_eq!(config.filter_mode, FilterMode::Fuzzy);
+        let config = Config::parse("sort = natural\ndirs_first = no").unwrap();
+        assert_eq!(config.sort.key, SortKey::Natural);
+        assert!(!config.sort.dirs_first);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Config::parse("symlinks = maybe").is_err());
+        assert!(Config::parse("colour = blue").is_err());
+        assert!(Config::parse("filter_mode = exact").is_err());
+        assert_eq!(
+            Config::parse("preserve_timestamps").unwrap_err(),
+            "line 1: expected 'key = value'"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossterm::event::{self, Event};
+
+use crate::app::{Entry, GitStatus, Preview};
+use crate::grep::GrepMatch;
+use crate::preview::PreviewKey;
+use crate::transfer::JobOutcome;
+use crate::watcher::Changes;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// How long the input thread waits for a terminal event before checking
+/// whether it has been paused
+const INPUT_POLL: Duration = Duration::from_millis(50);
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Something for the event loop to handle: user input or a result posted
+/// by a background worker.
+pub enum Message {
+    Input(Event),
+    /// Entries of `dir`, answering the request that was given `generation`.
+    Listing {
+        dir: PathBuf,
+        generation: u64,
+        entries: io::Result<Vec<Entry>>,
+    },
+    /// Output of `git status` for `dir`, answering the request that was
+    /// given `generation`.
+    GitStatus {
+        dir: PathBuf,
+        generation: u64,
+        statuses: HashMap<String, GitStatus>,
+    },
+    /// A preview loaded for the selection `key` describes.
+    Preview {
+        key: PreviewKey,
+        preview: Preview,
+    },
+    /// Paths the finder with id `finder` walked; `done` marks the last batch.
+    FinderPaths {
+        finder: u64,
+        paths: Vec<String>,
+        done: bool,
+    },
+    /// Matches of the content search with id `search`; `done` marks the
+    /// last message.
+    GrepMatches {
+        search: u64,
+        matches: Vec<GrepMatch>,
+        done: bool,
+    },
+    /// A copy or move job finished.
+    TransferFinished(JobOutcome),
+    /// Other programs changed the listing of `dir` or the preview.
+    FilesChanged { dir: PathBuf, changes: Changes },
+}
+
+/// The event loop's message channel. Terminal input is read on its own
+/// thread, so the loop never blocks on the keyboard.
+pub struct Events {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    /// Set while the terminal belongs to another program
+    paused: Arc<AtomicBool>,
+    /// Held by the input thread while it reads from the terminal
+    reading: Arc<Mutex<()>>,
+}
+
+/// Keeps the input thread away from the terminal until dropped.
+pub struct InputPause<'a> {
+    paused: &'a AtomicBool,
+    _reading: MutexGuard<'a, ()>,
+}
+
+// =============================================================================
+// Events Implementation
+// =============================================================================
+
+impl Events {
+    /// Creates the channel and starts reading terminal input.
+    pub fn start() -> Events {
+        let (sender, receiver) = mpsc::channel();
+        let paused = Arc::new(AtomicBool::new(false));
+        let reading = Arc::new(Mutex::new(()));
+
+        let input = sender.clone();
+        let stop = Arc::clone(&paused);
+        let lock = Arc::clone(&reading);
+        thread::spawn(move || loop {
+            if stop.load(Ordering::Relaxed) {
+                thread::sleep(INPUT_POLL);
+                continue;
+            }
+            let _reading = lock.lock().unwrap_or_else(|e| e.into_inner());
+            match event::poll(INPUT_POLL) {
+                Ok(true) => match event::read() {
+                    Ok(event) => {
+                        if input.send(Message::Input(event)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(_) => return,
+                },
+                Ok(false) => {}
+                Err(_) => return,
+            }
+        });
+
+        Events {
+            sender,
+            receiver,
+            paused,
+            reading,
+        }
+    }
+
+    /// A sender for workers to post their results with.
+    pub fn sender(&self) -> Sender<Message> {
+        self.sender.clone()
+    }
+
+    /// Waits up to `timeout` for the next message.
+    pub fn next(&self, timeout: Duration) -> Option<Message> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Messages that are already waiting, without blocking.
+    pub fn pending(&self) -> impl Iterator<Item = Message> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Stops reading terminal input, e.g. while an editor runs. Waits for
+    /// a read in progress to finish.
+    pub fn pause_input(&self) -> InputPause<'_> {
+        self.paused.store(true, Ordering::Relaxed);
+        InputPause {
+            paused: &self.paused,
+            _reading: self.reading.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+}
+
+impl Drop for InputPause<'_> {
+    fn drop(&mut self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+}
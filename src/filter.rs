@@ -0,0 +1,531 @@
+use std::time::{Duration, SystemTime};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::app::{Entry, GitStatus};
+use crate::fuzzy::fuzzy_match;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Attribute names recognised in filter queries
+const ATTRIBUTE_KEYS: [&str; 6] = ["size", "modified", "mtime", "type", "ext", "git"];
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// How the `/` filter matches entry names.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FilterMode {
+    /// The name contains the query.
+    #[default]
+    Substring,
+    /// The query's characters appear in order; best matches first.
+    Fuzzy,
+    /// Shell-style pattern like `*.rs` matching the whole name.
+    Glob,
+    /// Regular expression found anywhere in the name.
+    Regex,
+}
+
+/// A query compiled for one filter mode.
+pub enum Filter {
+    Substring(String),
+    Fuzzy(String),
+    Glob(Regex),
+    Regex(Regex),
+}
+
+/// A condition on entry metadata such as `size>10M` or `ext:rs,toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeFilter {
+    /// The condition as typed, shown as a chip in the header.
+    pub text: String,
+    condition: Condition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Size(Comparison, u64),
+    /// Compares the time since the last modification.
+    Age(Comparison, Duration),
+    IsDir(bool),
+    /// Lowercase extensions without the dot.
+    Extension(Vec<String>),
+    /// `None` stands for a clean (unchanged) entry.
+    Git(Vec<Option<GitStatus>>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// A filter query split into the name part and attribute conditions.
+#[derive(Debug, Default)]
+pub struct Query {
+    pub name: String,
+    pub attributes: Vec<AttributeFilter>,
+    /// Problem with the first malformed condition; such words are ignored.
+    pub error: Option<String>,
+}
+
+// =============================================================================
+// FilterMode Implementation
+// =============================================================================
+
+impl FilterMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterMode::Substring => "substring",
+            FilterMode::Fuzzy => "fuzzy",
+            FilterMode::Glob => "glob",
+            FilterMode::Regex => "regex",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<FilterMode> {
+        [
+            FilterMode::Substring,
+            FilterMode::Fuzzy,
+            FilterMode::Glob,
+            FilterMode::Regex,
+        ]
+        .into_iter()
+        .find(|mode| mode.label() == value)
+    }
+
+    pub fn next(self) -> FilterMode {
+        match self {
+            FilterMode::Substring => FilterMode::Fuzzy,
+            FilterMode::Fuzzy => FilterMode::Glob,
+            FilterMode::Glob => FilterMode::Regex,
+            FilterMode::Regex => FilterMode::Substring,
+        }
+    }
+}
+
+// =============================================================================
+// Filter Implementation
+// =============================================================================
+
+impl Filter {
+    /// Compiles `query`; fails on an invalid regex. Matching ignores case.
+    pub fn new(mode: FilterMode, query: &str) -> Result<Filter, String> {
+        match mode {
+            FilterMode::Substring => Ok(Filter::Substring(query.to_lowercase())),
+            FilterMode::Fuzzy => Ok(Filter::Fuzzy(query.to_string())),
+            FilterMode::Glob => build_regex(&glob_to_regex(query)).map(Filter::Glob),
+            FilterMode::Regex => build_regex(query).map(Filter::Regex),
+        }
+    }
+
+    /// Returns the score of a matching name (higher is better; only fuzzy
+    /// matching scores) and the char indices to highlight.
+    pub fn matches(&self, name: &str, name_lower: &str) -> Option<(i64, Vec<usize>)> {
+        match self {
+            Filter::Substring(query) => {
+                let start = name_lower.find(query.as_str())?;
+                let first = name_lower[..start].chars().count();
+                Some((0, (first..first + query.chars().count()).collect()))
+            }
+            Filter::Fuzzy(query) => fuzzy_match(query, name),
+            Filter::Regex(regex) => {
+                let found = regex.find(name)?;
+                Some((0, char_positions(name, found.start(), found.end())))
+            }
+            Filter::Glob(regex) => {
+                // Each literal run of the pattern is a capture group
+                let captures = regex.captures(name)?;
+                let positions = captures
+                    .iter()
+                    .skip(1)
+                    .flatten()
+                    .flat_map(|group| char_positions(name, group.start(), group.end()))
+                    .collect();
+                Some((0, positions))
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Attribute Filters
+// =============================================================================
+
+impl Query {
+    /// Splits `text` at whitespace. Words starting with an attribute name
+    /// and an operator (`size>10M`, `type:dir`) become conditions; the rest
+    /// is the name query.
+    pub fn parse(text: &str) -> Query {
+        let mut query = Query::default();
+        if !text.split_whitespace().any(|word| attribute_key(word).is_some()) {
+            query.name = text.to_string();
+            return query;
+        }
+        let mut words = Vec::new();
+        for word in text.split_whitespace() {
+            if attribute_key(word).is_none() {
+                words.push(word);
+                continue;
+            }
+            match AttributeFilter::parse(word) {
+                Ok(filter) => query.attributes.push(filter),
+                Err(e) => {
+                    query.error.get_or_insert(e);
+                }
+            }
+        }
+        query.name = words.join(" ");
+        query
+    }
+}
+
+impl AttributeFilter {
+    pub fn parse(text: &str) -> Result<AttributeFilter, String> {
+        let (key, rest) = attribute_key(text).ok_or_else(|| format!("Unknown filter '{}'", text))?;
+        let (comparison, value) = split_operator(rest);
+        if value.is_empty() {
+            return Err(format!("'{}' needs a value", text));
+        }
+        let ordered = |kind: &str| match comparison {
+            Some(comparison) => Ok(comparison),
+            None => Err(format!("{} filters compare with < or >, e.g. {}", key, kind)),
+        };
+        let exact = || match comparison {
+            Some(Comparison::Equal) | None => Ok(()),
+            Some(_) => Err(format!("{} filters take ':', e.g. {}:{}", key, key, value)),
+        };
+
+        let condition = match key {
+            "size" => Condition::Size(ordered("size>10M")?, parse_size(value)?),
+            "modified" | "mtime" => Condition::Age(ordered("modified<7d")?, parse_age(value)?),
+            "type" => {
+                exact()?;
+                Condition::IsDir(match value {
+                    "dir" | "d" => true,
+                    "file" | "f" => false,
+                    _ => return Err(format!("type must be dir or file, got '{}'", value)),
+                })
+            }
+            "ext" => {
+                exact()?;
+                let extensions = value
+                    .split(',')
+                    .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                    .collect();
+                Condition::Extension(extensions)
+            }
+            _ => {
+                exact()?;
+                let statuses = value
+                    .split(',')
+                    .map(parse_git_status)
+                    .collect::<Result<_, _>>()?;
+                Condition::Git(statuses)
+            }
+        };
+        Ok(AttributeFilter {
+            text: text.to_string(),
+            condition,
+        })
+    }
+
+    /// True if `entry` satisfies the condition, measuring ages from `now`.
+    /// Size and extension conditions only apply to files, so directories
+    /// stay reachable while they are active.
+    pub fn matches(&self, entry: &Entry, now: SystemTime) -> bool {
+        match &self.condition {
+            Condition::Size(comparison, size) => {
+                entry.is_dir || comparison.holds(entry.size, *size)
+            }
+            Condition::Age(comparison, age) => entry
+                .modified
+                .map(|modified| now.duration_since(modified).unwrap_or_default())
+                .is_some_and(|actual| comparison.holds(actual, *age)),
+            Condition::IsDir(is_dir) => entry.is_dir == *is_dir,
+            Condition::Extension(extensions) => {
+                entry.is_dir
+                    || entry
+                        .name_lower
+                        .rsplit_once('.')
+                        .filter(|(stem, _)| !stem.is_empty())
+                        .is_some_and(|(_, ext)| extensions.iter().any(|e| e == ext))
+            }
+            Condition::Git(statuses) => statuses.contains(&entry.git_status),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds<T: PartialOrd>(self, actual: T, expected: T) -> bool {
+        match self {
+            Comparison::Less => actual < expected,
+            Comparison::LessOrEqual => actual <= expected,
+            Comparison::Equal => actual == expected,
+            Comparison::GreaterOrEqual => actual >= expected,
+            Comparison::Greater => actual > expected,
+        }
+    }
+}
+
+/// The attribute name at the start of `word` if an operator follows it,
+/// with the rest of the word.
+fn attribute_key(word: &str) -> Option<(&'static str, &str)> {
+    ATTRIBUTE_KEYS.into_iter().find_map(|key| {
+        let rest = word.strip_prefix(key)?;
+        rest.starts_with([':', '=', '<', '>']).then_some((key, rest))
+    })
+}
+
+/// Splits the operator off `rest`; `:` means the same as `=` and yields
+/// `None` so exact-only attributes can accept it.
+fn split_operator(rest: &str) -> (Option<Comparison>, &str) {
+    let operators = [
+        (">=", Some(Comparison::GreaterOrEqual)),
+        ("<=", Some(Comparison::LessOrEqual)),
+        (">", Some(Comparison::Greater)),
+        ("<", Some(Comparison::Less)),
+        ("=", Some(Comparison::Equal)),
+        (":", None),
+    ];
+    for (operator, comparison) in operators {
+        if let Some(value) = rest.strip_prefix(operator) {
+            return (comparison, value);
+        }
+    }
+    (None, rest)
+}
+
+/// Parses sizes like `512`, `10k`, `1.5M` or `2GB` (powers of 1024).
+fn parse_size(value: &str) -> Result<u64, String> {
+    let lower = value.to_ascii_lowercase();
+    let digits_end = lower
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(digits_end);
+    let multiplier: u64 = match unit.trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return Err(format!("Unknown size unit in '{}' (use K, M, G or T)", value)),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a size", value))?;
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Parses ages like `30m`, `12h`, `7d`, `2w` or `1y`.
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let digits_end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits_end);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not an age like 7d", value))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" | "min" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return Err(format!("Unknown age unit in '{}' (use s, m, h, d, w or y)", value)),
+    };
+    Ok(Duration::from_secs(number.saturating_mul(seconds)))
+}
+
+fn parse_git_status(value: &str) -> Result<Option<GitStatus>, String> {
+    match value {
+        "modified" | "m" => Ok(Some(GitStatus::Modified)),
+        "staged" | "s" => Ok(Some(GitStatus::Staged)),
+        "untracked" | "u" => Ok(Some(GitStatus::Untracked)),
+        "ignored" | "i" => Ok(Some(GitStatus::Ignored)),
+        "conflict" | "c" => Ok(Some(GitStatus::Conflict)),
+        "clean" => Ok(None),
+        _ => Err(format!(
+            "git status must be modified, staged, untracked, ignored, conflict or clean, got '{}'",
+            value
+        )),
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn build_regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| {
+            // Parse errors span several lines, ending with the explanation
+            let text = e.to_string();
+            let reason = text.lines().last().unwrap_or_default();
+            format!("Invalid pattern: {}", reason.trim_start_matches("error: "))
+        })
+}
+
+/// Translates a glob into an anchored regex. `*` matches any run of
+/// characters, `?` one character and `[...]` a character class; literal
+/// runs become capture groups so they can be highlighted.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut literal = String::new();
+    let mut chars = glob.chars();
+
+    let flush = |regex: &mut String, literal: &mut String| {
+        if !literal.is_empty() {
+            regex.push('(');
+            regex.push_str(&regex::escape(literal));
+            regex.push(')');
+            literal.clear();
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                flush(&mut regex, &mut literal);
+                regex.push_str(".*");
+            }
+            '?' => {
+                flush(&mut regex, &mut literal);
+                regex.push('.');
+            }
+            '[' => {
+                flush(&mut regex, &mut literal);
+                let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let class = match class.strip_prefix('!') {
+                    Some(rest) => format!("^{}", rest),
+                    None => class,
+                };
+                regex.push('[');
+                regex.push_str(&class.replace('\\', "\\\\").replace('[', "\\["));
+                regex.push(']');
+            }
+            c => literal.push(c),
+        }
+    }
+    flush(&mut regex, &mut literal);
+    regex.push('$');
+    regex
+}
+
+/// Char indices covered by the byte range `start..end` of `text`.
+fn char_positions(text: &str, start: usize, end: usize) -> Vec<usize> {
+    text.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, _))| (start..end).contains(byte))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(mode: FilterMode, query: &str, name: &str) -> Option<Vec<usize>> {
+        let filter = Filter::new(mode, query).unwrap();
+        filter
+            .matches(name, &name.to_lowercase())
+            .map(|(_, positions)| positions)
+    }
+
+    #[test]
+    fn test_substring_and_regex() {
+        assert_eq!(positions(FilterMode::Substring, "AIN", "main.rs"), Some(vec![1, 2, 3]));
+        assert_eq!(positions(FilterMode::Substring, "x", "main.rs"), None);
+        assert_eq!(positions(FilterMode::Regex, r"\d+", "été42.txt"), Some(vec![3, 4]));
+        assert!(Filter::new(FilterMode::Regex, "(").is_err());
+    }
+
+    #[test]
+    fn test_glob() {
+        assert_eq!(positions(FilterMode::Glob, "*.rs", "main.rs"), Some(vec![4, 5, 6]));
+        assert_eq!(positions(FilterMode::Glob, "*.rs", "main.rs.bak"), None);
+        assert!(positions(FilterMode::Glob, "file?.[ch]", "FILE1.c").is_some());
+        assert!(positions(FilterMode::Glob, "[!a]*", "abc").is_none());
+        assert!(positions(FilterMode::Glob, "a+b", "a+b").is_some());
+    }
+
+    fn entry(name: &str, is_dir: bool, size: u64, age_days: u64) -> Entry {
+        Entry {
+            name: name.into(),
+            name_lower: name.to_lowercase(),
+            is_dir,
+            size,
+            modified: SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(
+                (1000 - age_days) * 24 * 60 * 60,
+            )),
+            is_hidden: false,
+            readonly: false,
+            git_status: None,
+        }
+    }
+
+    #[test]
+    fn test_attribute_filters() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000 * 24 * 60 * 60);
+        let matches = |text: &str, entry: &Entry| {
+            AttributeFilter::parse(text).unwrap().matches(entry, now)
+        };
+        let big = entry("Video.MKV", false, 20 << 20, 30);
+        let small = entry("main.rs", false, 100, 1);
+        let dir = entry("src.rs", true, 4096, 1);
+
+        assert!(matches("size>10M", &big) && !matches("size>10M", &small));
+        assert!(matches("size<=1.5k", &small) && matches("size>1G", &dir));
+        assert!(matches("modified<7d", &small) && !matches("modified<7d", &big));
+        assert!(matches("mtime>=4w", &big));
+        assert!(matches("type:dir", &dir) && !matches("type:dir", &small));
+        assert!(matches("ext:rs,toml", &small) && !matches("ext:rs", &big));
+        assert!(matches("ext:mkv", &big) && matches("ext:mkv", &dir));
+        assert!(matches("git:clean", &small) && !matches("git:modified", &small));
+
+        assert!(AttributeFilter::parse("size:10M").is_err());
+        assert!(AttributeFilter::parse("size>10Q").is_err());
+        assert!(AttributeFilter::parse("type>dir").is_err());
+        assert!(AttributeFilter::parse("git:dirty").is_err());
+    }
+
+    #[test]
+    fn test_query_split() {
+        let query = Query::parse("foo  bar");
+        assert_eq!(query.name, "foo  bar");
+        assert!(query.attributes.is_empty());
+
+        let query = Query::parse("type:file main size>1k ext:");
+        assert_eq!(query.name, "main");
+        assert_eq!(query.attributes.len(), 2);
+        assert_eq!(query.attributes[1].text, "size>1k");
+        assert_eq!(query.error.as_deref(), Some("'ext:' needs a value"));
+
+        // Words that only start like an attribute stay part of the name
+        assert_eq!(Query::parse("sizes typeface").name, "sizes typeface");
+    }
+
+    #[test]
+    fn test_mode_labels() {
+        let mut mode = FilterMode::default();
+        for _ in 0..4 {
+            assert_eq!(FilterMode::parse(mode.label()), Some(mode));
+            mode = mode.next();
+        }
+        assert_eq!(mode, FilterMode::Substring);
+    }
+}
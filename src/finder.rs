@@ -0,0 +1,213 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    thread,
+};
+
+use ignore::WalkBuilder;
+
+use crate::events::Message;
+use crate::fuzzy::fuzzy_match;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Paths are sent from the walker in batches of this size
+const BATCH_SIZE: usize = 512;
+
+/// The walk stops after this many paths
+const MAX_PATHS: usize = 200_000;
+
+/// Maximum number of results shown
+const MAX_RESULTS: usize = 500;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Project-wide file finder. The tree below `root` is walked in a
+/// background thread, skipping what `.gitignore` files exclude.
+pub struct Finder {
+    /// Tells the paths of this walk from those of an earlier one
+    pub id: u64,
+    pub root: PathBuf,
+    /// Paths found so far, relative to `root`.
+    paths: Vec<String>,
+    cancel: Arc<AtomicBool>,
+    done: bool,
+}
+
+/// A path matching the finder query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinderMatch {
+    /// Path relative to the finder root.
+    pub path: String,
+    pub score: i64,
+    /// Char indices of the matched characters in `path`.
+    pub positions: Vec<usize>,
+}
+
+// =============================================================================
+// Finder Implementation
+// =============================================================================
+
+impl Finder {
+    /// Starts walking `root`. Hidden files are included only if `show_hidden`.
+    /// The paths are posted to `sender` in batches as `Message::FinderPaths`
+    /// carrying `id`; the last one is marked done.
+    pub fn start(id: u64, root: PathBuf, show_hidden: bool, sender: Sender<Message>) -> Finder {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let walker = WalkBuilder::new(&root)
+            .hidden(!show_hidden)
+            .git_ignore(true)
+            // Honour .gitignore files outside of git repositories too
+            .require_git(false)
+            .build();
+        let prefix = root.clone();
+        let stop = Arc::clone(&cancel);
+
+        thread::spawn(move || {
+            let post = |paths, done| {
+                let message = Message::FinderPaths {
+                    finder: id,
+                    paths,
+                    done,
+                };
+                sender.send(message).is_ok()
+            };
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            let mut total = 0;
+            for entry in walker {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if total >= MAX_PATHS {
+                    break;
+                }
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                let Some(relative) = relative_path(&prefix, entry.path()) else {
+                    continue;
+                };
+                batch.push(relative);
+                total += 1;
+                if batch.len() == BATCH_SIZE && !post(std::mem::take(&mut batch), false) {
+                    return;
+                }
+            }
+            post(batch, true);
+        });
+
+        Finder {
+            id,
+            root,
+            paths: Vec::new(),
+            cancel,
+            done: false,
+        }
+    }
+
+    /// Adds a batch of paths the walker posted; `done` marks the last one.
+    pub fn add(&mut self, paths: Vec<String>, done: bool) {
+        self.paths.extend(paths);
+        self.done = done;
+    }
+
+    /// True once the walk has finished.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Best matches for `query`, best first. An empty query lists paths in
+    /// walk order.
+    pub fn search(&self, query: &str) -> Vec<FinderMatch> {
+        let mut matches: Vec<FinderMatch> = self
+            .paths
+            .iter()
+            .filter_map(|path| {
+                fuzzy_match(query, path).map(|(score, positions)| FinderMatch {
+                    path: path.clone(),
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+        // Stable sort keeps walk order among equal scores, shorter paths first
+        matches.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.len().cmp(&b.path.len())));
+        matches.truncate(MAX_RESULTS);
+        matches
+    }
+}
+
+impl Drop for Finder {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// `path` relative to `root` with '/' separators; None for `root` itself.
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::{fs, sync::mpsc, time::Duration};
+
+    #[test]
+    fn test_walk_respects_gitignore_and_hidden() {
+        let dir = temp_dir("finder");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "").unwrap();
+        fs::write(dir.join("target/main.o"), "").unwrap();
+        fs::write(dir.join(".env"), "").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut finder = Finder::start(1, dir.clone(), false, sender);
+        while !finder.is_done() {
+            match receiver.recv_timeout(Duration::from_secs(5)) {
+                Ok(Message::FinderPaths { paths, done, .. }) => finder.add(paths, done),
+                _ => panic!("walk did not finish"),
+            }
+        }
+        let mut paths = finder.paths.clone();
+        paths.sort();
+        assert_eq!(paths, vec!["src", "src/main.rs"]);
+
+        let results = finder.search("mrs");
+        assert_eq!(results[0].path, "src/main.rs");
+        assert_eq!(results[0].positions, vec![4, 9, 10]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,331 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Name of the database file inside the data directory
+const DATABASE_FILE_NAME: &str = "frecency";
+
+/// Once the ranks add up to more than this, all of them are aged
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+/// Factor applied to every rank when aging
+const AGING_FACTOR: f64 = 0.9;
+
+/// Entries whose rank drops below this after aging are forgotten
+const MIN_RANK: f64 = 1.0;
+
+/// While browsing, the database is written at most this often
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Most directories a query returns
+const MAX_QUERY_RESULTS: usize = 50;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Directories ranked by how often and how recently they were visited,
+/// persisted to `$XDG_DATA_HOME/fylins/frecency`.
+#[derive(Debug, Default)]
+pub struct Frecency {
+    entries: Vec<Visit>,
+    /// File the database is saved to; None keeps it in memory only.
+    file: Option<PathBuf>,
+    /// Set while there are visits the file does not have yet
+    dirty: bool,
+    last_save: Option<Instant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Visit {
+    path: PathBuf,
+    rank: f64,
+    /// Seconds since the Unix epoch.
+    last_access: u64,
+}
+
+// =============================================================================
+// Frecency Implementation
+// =============================================================================
+
+impl Frecency {
+    /// Loads the database in `dir` (usually the data directory). A missing
+    /// file yields an empty database; an unreadable one yields an empty
+    /// database plus a description of the problem. Without a directory the
+    /// database is kept in memory only.
+    pub fn load(dir: Option<&Path>) -> (Frecency, Option<String>) {
+        let file = dir.map(|dir| dir.join(DATABASE_FILE_NAME));
+        let mut frecency = Frecency {
+            file: file.clone(),
+            ..Frecency::default()
+        };
+        let Some(file) = file else {
+            return (frecency, None);
+        };
+        match fs::read_to_string(&file) {
+            Ok(text) => {
+                frecency.entries = parse(&text);
+                (frecency, None)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (frecency, None),
+            Err(e) => (
+                frecency,
+                Some(format!("Cannot read jump database {}: {}", file.display(), e)),
+            ),
+        }
+    }
+
+    /// Records a visit to `path`. The database is saved unless it was
+    /// saved less than `SAVE_INTERVAL` ago; `flush` saves the rest.
+    pub fn add(&mut self, path: &Path) -> io::Result<()> {
+        self.add_at(path, now());
+        self.save_if_due()
+    }
+
+    /// Forgets `path`, e.g. after it turned out to be gone.
+    pub fn remove(&mut self, path: &Path) -> io::Result<()> {
+        self.entries.retain(|v| v.path != path);
+        self.dirty = true;
+        self.save_if_due()
+    }
+
+    /// Saves changes that are still waiting, e.g. on exit.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    /// Existing directories matching every keyword of `query`, best first.
+    /// `exclude` (usually the current directory) is never returned.
+    pub fn query(&self, query: &str, exclude: &Path) -> Vec<PathBuf> {
+        let keywords: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let now = now();
+        let mut matches: Vec<(f64, &Path)> = self
+            .entries
+            .iter()
+            .filter(|v| v.path != exclude && matches(&v.path, &keywords))
+            .map(|v| (score(v, now), v.path.as_path()))
+            .collect();
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+        // Only stat until the list is full
+        matches
+            .into_iter()
+            .map(|(_, path)| path)
+            .filter(|path| path.is_dir())
+            .take(MAX_QUERY_RESULTS)
+            .map(Path::to_path_buf)
+            .collect()
+    }
+
+    fn add_at(&mut self, path: &Path, now: u64) {
+        self.dirty = true;
+        match self.entries.iter_mut().find(|v| v.path == path) {
+            Some(visit) => {
+                visit.rank += 1.0;
+                visit.last_access = now;
+            }
+            None => self.entries.push(Visit {
+                path: path.to_path_buf(),
+                rank: 1.0,
+                last_access: now,
+            }),
+        }
+
+        // Age old entries so the database stays small and current
+        if self.entries.iter().map(|v| v.rank).sum::<f64>() > MAX_TOTAL_RANK {
+            for visit in &mut self.entries {
+                visit.rank *= AGING_FACTOR;
+            }
+            self.entries.retain(|v| v.rank >= MIN_RANK);
+        }
+    }
+
+    fn save_if_due(&mut self) -> io::Result<()> {
+        if self.last_save.is_some_and(|t| t.elapsed() < SAVE_INTERVAL) {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        // A failed save is retried by the next one due, not on every visit
+        self.last_save = Some(Instant::now());
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write then rename, so a crash never leaves a truncated database
+        let temp = file.with_extension("tmp");
+        fs::write(&temp, format(&self.entries))?;
+        fs::rename(&temp, file)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// True if the keywords appear in `path` in order, case-insensitively,
+/// with the last one inside the final path component.
+fn matches(path: &Path, keywords: &[String]) -> bool {
+    let Some(last) = keywords.last() else {
+        return true;
+    };
+    let path = path.to_string_lossy().to_lowercase();
+    let mut rest = path.as_str();
+    for keyword in keywords {
+        match rest.find(keyword.as_str()) {
+            Some(i) => rest = &rest[i + keyword.len()..],
+            None => return false,
+        }
+    }
+
+    let name_start = path.rfind(std::path::MAIN_SEPARATOR).map_or(0, |i| i + 1);
+    path[name_start..].contains(last.as_str())
+}
+
+/// Rank weighted by how recently the directory was visited.
+fn score(visit: &Visit, now: u64) -> f64 {
+    let age = now.saturating_sub(visit.last_access);
+    let weight = if age < HOUR {
+        4.0
+    } else if age < DAY {
+        2.0
+    } else if age < WEEK {
+        0.5
+    } else {
+        0.25
+    };
+    visit.rank * weight
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Parses `rank<TAB>last_access<TAB>path` lines, skipping broken ones.
+fn parse(text: &str) -> Vec<Visit> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let rank = fields.next()?.parse().ok()?;
+            let last_access = fields.next()?.parse().ok()?;
+            let path = fields.next().filter(|p| !p.is_empty())?;
+            Some(Visit {
+                path: PathBuf::from(path),
+                rank,
+                last_access,
+            })
+        })
+        .collect()
+}
+
+fn format(entries: &[Visit]) -> String {
+    entries
+        .iter()
+        .map(|v| format!("{}\t{}\t{}\n", v.rank, v.last_access, v.path.display()))
+        .collect()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn keywords(query: &str) -> Vec<String> {
+        query.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_matches_keywords_in_order() {
+        let path = Path::new("/home/me/work/project/api");
+        assert!(matches(path, &keywords("proj api")));
+        assert!(matches(path, &keywords("api")));
+        assert!(matches(path, &keywords("w p a")));
+        assert!(!matches(path, &keywords("api proj")));
+        // The last keyword must match the final component
+        assert!(!matches(path, &keywords("proj")));
+    }
+
+    #[test]
+    fn test_score_prefers_frequent_and_recent() {
+        let now = 10 * WEEK;
+        let mut frecency = Frecency::default();
+        frecency.add_at(Path::new("/a/old"), now - 2 * WEEK);
+        frecency.add_at(Path::new("/a/old"), now - 2 * WEEK);
+        frecency.add_at(Path::new("/a/new"), now);
+
+        let old = &frecency.entries[0];
+        let new = &frecency.entries[1];
+        assert_eq!(old.rank, 2.0);
+        assert!(score(new, now) > score(old, now));
+    }
+
+    #[test]
+    fn test_aging_forgets_rare_entries() {
+        let mut frecency = Frecency::default();
+        frecency.entries.push(Visit {
+            path: PathBuf::from("/rare"),
+            rank: 1.0,
+            last_access: 0,
+        });
+        frecency.entries.push(Visit {
+            path: PathBuf::from("/often"),
+            rank: MAX_TOTAL_RANK,
+            last_access: 0,
+        });
+        frecency.add_at(Path::new("/often"), 1);
+        assert_eq!(frecency.entries.len(), 1);
+        assert_eq!(frecency.entries[0].path, PathBuf::from("/often"));
+    }
+
+    #[test]
+    fn test_saves_are_batched() {
+        let dir = temp_dir("frecency");
+        let file = dir.join(DATABASE_FILE_NAME);
+        let (mut frecency, error) = Frecency::load(Some(&dir));
+        assert!(error.is_none());
+        let saved = || parse(&fs::read_to_string(&file).unwrap()).len();
+
+        frecency.add(&dir.join("a")).unwrap();
+        assert_eq!(saved(), 1);
+        // Visits right after a save wait for the next one
+        frecency.add(&dir.join("b")).unwrap();
+        assert_eq!(saved(), 1);
+        frecency.flush().unwrap();
+        assert_eq!(saved(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_and_format_roundtrip() {
+        let text = "2.5\t100\t/home/me/my project\nbroken line\n";
+        let entries = parse(text);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(format(&entries), "2.5\t100\t/home/me/my project\n");
+    }
+}
@@ -0,0 +1,149 @@
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Score for every matched character
+const SCORE_MATCH: i64 = 16;
+
+/// Bonus for a match right after a path separator
+const BONUS_PATH_START: i64 = 12;
+
+/// Bonus for a match at the start of a word (`_`, `-`, `.`, space, camelCase)
+const BONUS_WORD_START: i64 = 8;
+
+/// Bonus for a match directly following the previous one
+const BONUS_CONSECUTIVE: i64 = 6;
+
+/// Bonus when every match lies in the final path component
+const BONUS_FILE_NAME: i64 = 20;
+
+/// Penalty per skipped character between two matches, capped per gap
+const PENALTY_GAP: i64 = 1;
+const MAX_GAP_PENALTY: i64 = 6;
+
+// =============================================================================
+// Matching
+// =============================================================================
+
+/// Matches `query` against `candidate` case-insensitively: every query
+/// character has to appear in order. Returns the score (higher is better)
+/// and the char indices of the matched characters in `candidate`.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lower)
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let lowered: Vec<char> = chars.iter().copied().map(lower).collect();
+
+    // Find where the first complete match ends...
+    let mut qi = 0;
+    let mut end = None;
+    for (i, c) in lowered.iter().enumerate() {
+        if *c == query[qi] {
+            qi += 1;
+            if qi == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    // ...then walk back from there to find the tightest window
+    let mut qi = query.len();
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if lowered[i] == query[qi - 1] {
+            qi -= 1;
+            if qi == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut qi = 0;
+    for (i, c) in lowered.iter().enumerate().take(end + 1).skip(start) {
+        if qi < query.len() && *c == query[qi] {
+            positions.push(i);
+            qi += 1;
+        }
+    }
+
+    Some((score(&chars, &positions), positions))
+}
+
+fn score(chars: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    for &pos in positions {
+        score += SCORE_MATCH;
+        let before = pos.checked_sub(1).map(|i| chars[i]);
+        score += match before {
+            None | Some('/') | Some('\\') => BONUS_PATH_START,
+            Some('_' | '-' | '.' | ' ') => BONUS_WORD_START,
+            Some(b) if b.is_lowercase() && chars[pos].is_uppercase() => BONUS_WORD_START,
+            _ => 0,
+        };
+        if let Some(prev) = previous {
+            let gap = (pos - prev - 1) as i64;
+            if gap == 0 {
+                score += BONUS_CONSECUTIVE;
+            } else {
+                score -= (gap * PENALTY_GAP).min(MAX_GAP_PENALTY);
+            }
+        }
+        previous = Some(pos);
+    }
+
+    let name_start = chars
+        .iter()
+        .rposition(|c| *c == '/' || *c == '\\')
+        .map_or(0, |i| i + 1);
+    if positions.first().is_some_and(|&first| first >= name_start) {
+        score += BONUS_FILE_NAME;
+    }
+    score
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_positions() {
+        let (_, positions) = fuzzy_match("abc", "xaxbxc").unwrap();
+        assert_eq!(positions, vec![1, 3, 5]);
+        assert!(fuzzy_match("cba", "abc").is_none());
+        assert_eq!(fuzzy_match("", "abc"), Some((0, Vec::new())));
+
+        // The tightest window wins over the first occurrence
+        let (_, positions) = fuzzy_match("ab", "a___ab").unwrap();
+        assert_eq!(positions, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let score = |query, candidate| fuzzy_match(query, candidate).unwrap().0;
+        // Word starts and file names beat scattered matches
+        assert!(score("mr", "src/main.rs") > score("mr", "src/numbers.txt"));
+        assert!(score("app", "src/app.rs") > score("app", "apps/x/readme"));
+        assert!(score("fb", "FooBar") > score("fb", "xfxxbx"));
+        // Case does not matter
+        assert!(fuzzy_match("README", "docs/readme.md").is_some());
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    fs,
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    thread,
+};
+
+use ignore::WalkBuilder;
+
+use crate::app::is_text;
+use crate::events::Message;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Bytes read from the start of a file to decide whether it is text
+const TEXT_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Files larger than this are not searched
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// The search stops after this many matches
+const MAX_MATCHES: usize = 5_000;
+
+/// Snippets are cut to this many characters
+const MAX_SNIPPET_CHARS: usize = 200;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Search of file contents below `root`, run in a background thread. Binary
+/// files and whatever `.gitignore` files exclude are skipped.
+pub struct ContentSearch {
+    /// Tells the matches of this search from those of an earlier one
+    pub id: u64,
+    pub root: PathBuf,
+    pub query: String,
+    pub matches: Vec<GrepMatch>,
+    cancel: Arc<AtomicBool>,
+    done: bool,
+}
+
+/// A line containing the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrepMatch {
+    /// Path relative to the search root, with '/' separators.
+    pub path: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// The line with leading whitespace removed, cut to a readable length.
+    pub snippet: String,
+    /// Byte range of the match within the full line.
+    pub range: Range<usize>,
+}
+
+// =============================================================================
+// ContentSearch Implementation
+// =============================================================================
+
+impl ContentSearch {
+    /// Starts searching the files below `root` for `query`. The match is
+    /// case-insensitive unless the query contains an uppercase letter.
+    /// Matches are posted to `sender` as `Message::GrepMatches` carrying
+    /// `id`; the last message is marked done.
+    pub fn start(
+        id: u64,
+        root: PathBuf,
+        query: String,
+        show_hidden: bool,
+        sender: Sender<Message>,
+    ) -> ContentSearch {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let walker = WalkBuilder::new(&root)
+            .hidden(!show_hidden)
+            .git_ignore(true)
+            .require_git(false)
+            .build();
+        let prefix = root.clone();
+        let needle = query.clone();
+        let stop = Arc::clone(&cancel);
+
+        thread::spawn(move || {
+            let matcher = Matcher::new(&needle);
+            let mut total = 0;
+            for entry in walker {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if total >= MAX_MATCHES {
+                    break;
+                }
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    continue;
+                }
+                let Ok(relative) = entry.path().strip_prefix(&prefix) else {
+                    continue;
+                };
+                let relative = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let found = search_file(entry.path(), &relative, &matcher);
+                total += found.len();
+                if !found.is_empty() && !post(&sender, id, found, false) {
+                    return;
+                }
+            }
+            post(&sender, id, Vec::new(), true);
+        });
+
+        ContentSearch {
+            id,
+            root,
+            query,
+            matches: Vec::new(),
+            cancel,
+            done: false,
+        }
+    }
+
+    /// Adds matches the search posted; `done` marks the last message.
+    pub fn add(&mut self, matches: Vec<GrepMatch>, done: bool) {
+        self.matches.extend(matches);
+        self.matches.truncate(MAX_MATCHES);
+        self.done = done;
+    }
+
+    /// True once every file has been searched.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl Drop for ContentSearch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+// =============================================================================
+// Matching
+// =============================================================================
+
+/// Smart-case substring matcher.
+pub struct Matcher {
+    needle: String,
+    ignore_case: bool,
+}
+
+impl Matcher {
+    pub fn new(query: &str) -> Matcher {
+        let ignore_case = !query.chars().any(char::is_uppercase);
+        Matcher {
+            needle: if ignore_case {
+                query.to_ascii_lowercase()
+            } else {
+                query.to_string()
+            },
+            ignore_case,
+        }
+    }
+
+    /// Byte range of the first match in `line`.
+    pub fn find(&self, line: &str) -> Option<Range<usize>> {
+        if self.needle.is_empty() {
+            return None;
+        }
+        // ASCII lowercasing keeps byte offsets valid for the original line
+        let start = if self.ignore_case {
+            line.to_ascii_lowercase().find(&self.needle)?
+        } else {
+            line.find(&self.needle)?
+        };
+        Some(start..start + self.needle.len())
+    }
+}
+
+fn search_file(path: &Path, relative: &str, matcher: &Matcher) -> Vec<GrepMatch> {
+    let Ok(mut file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    if file.metadata().map_or(true, |m| m.len() > MAX_FILE_BYTES) {
+        return Vec::new();
+    }
+    let mut data = Vec::new();
+    if file.read_to_end(&mut data).is_err() {
+        return Vec::new();
+    }
+    if !is_text(&data[..data.len().min(TEXT_SNIFF_BYTES)]) {
+        return Vec::new();
+    }
+
+    String::from_utf8_lossy(&data)
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let range = matcher.find(line)?;
+            Some(GrepMatch {
+                path: relative.to_string(),
+                line: i + 1,
+                snippet: line.trim_start().chars().take(MAX_SNIPPET_CHARS).collect(),
+                range,
+            })
+        })
+        .collect()
+}
+
+/// Posts matches of search `id`; false if the event loop is gone.
+fn post(sender: &Sender<Message>, id: u64, matches: Vec<GrepMatch>, done: bool) -> bool {
+    let message = Message::GrepMatches {
+        search: id,
+        matches,
+        done,
+    };
+    sender.send(message).is_ok()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::{sync::mpsc, time::Duration};
+
+    #[test]
+    fn test_smart_case() {
+        assert_eq!(Matcher::new("todo").find("// TODO: fix"), Some(3..7));
+        assert_eq!(Matcher::new("Todo").find("// TODO: fix"), None);
+        assert_eq!(Matcher::new("TODO").find("é TODO"), Some(3..7));
+        assert_eq!(Matcher::new("").find("anything"), None);
+    }
+
+    #[test]
+    fn test_search_skips_binary_files() {
+        let dir = temp_dir("grep");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "fn main() {}\n    let needle = 1;\n").unwrap();
+        fs::write(dir.join("blob.bin"), b"needle\x00\x01\x02\x03\x04\x05\x06").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut search = ContentSearch::start(1, dir.clone(), "needle".to_string(), false, sender);
+        while !search.is_done() {
+            match receiver.recv_timeout(Duration::from_secs(5)) {
+                Ok(Message::GrepMatches { matches, done, .. }) => search.add(matches, done),
+                _ => panic!("search did not finish"),
+            }
+        }
+        assert_eq!(
+            search.matches,
+            vec![GrepMatch {
+                path: "src/lib.rs".to_string(),
+                line: 2,
+                snippet: "let needle = 1;".to_string(),
+                range: 8..14,
+            }]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
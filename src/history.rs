@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Maximum number of entries kept on each of the back/forward stacks
+const MAX_HISTORY_ENTRIES: usize = 200;
+
+/// Maximum number of directories listed in the recent popup
+const MAX_RECENT_DIRS: usize = 50;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Browser-style back/forward stacks plus a most-recently-used list of
+/// directories, kept for the current session.
+#[derive(Debug, Default)]
+pub struct History {
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+    /// Visited directories, most recent first, without duplicates.
+    recent: Vec<PathBuf>,
+}
+
+// =============================================================================
+// History Implementation
+// =============================================================================
+
+impl History {
+    /// Records a navigation from `from` to `to`. Clears the forward stack.
+    pub fn visit(&mut self, from: &Path, to: &Path) {
+        if from == to {
+            return;
+        }
+        push_capped(&mut self.back, from.to_path_buf());
+        self.forward.clear();
+        self.touch(from);
+        self.touch(to);
+    }
+
+    /// Pops the directory to go back to; `current` becomes reachable by
+    /// going forward.
+    pub fn back(&mut self, current: &Path) -> Option<PathBuf> {
+        let target = self.back.pop()?;
+        push_capped(&mut self.forward, current.to_path_buf());
+        self.touch(&target);
+        Some(target)
+    }
+
+    /// Pops the directory to go forward to; `current` becomes reachable by
+    /// going back.
+    pub fn forward(&mut self, current: &Path) -> Option<PathBuf> {
+        let target = self.forward.pop()?;
+        push_capped(&mut self.back, current.to_path_buf());
+        self.touch(&target);
+        Some(target)
+    }
+
+    /// Reverts the last `back` or `forward` call after the target turned out
+    /// to be unreachable. The target is dropped from the history.
+    pub fn discard(&mut self, target: &Path, went_back: bool) {
+        if went_back {
+            self.forward.pop();
+        } else {
+            self.back.pop();
+        }
+        self.recent.retain(|p| p != target);
+    }
+
+    pub fn recent(&self) -> &[PathBuf] {
+        &self.recent
+    }
+
+    /// Moves `path` to the front of the recent list.
+    fn touch(&mut self, path: &Path) {
+        self.recent.retain(|p| p != path);
+        self.recent.insert(0, path.to_path_buf());
+        self.recent.truncate(MAX_RECENT_DIRS);
+    }
+}
+
+fn push_capped(stack: &mut Vec<PathBuf>, path: PathBuf) {
+    stack.push(path);
+    if stack.len() > MAX_HISTORY_ENTRIES {
+        stack.remove(0);
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_back_and_forward() {
+        let (a, b, c) = (Path::new("/a"), Path::new("/b"), Path::new("/c"));
+        let mut history = History::default();
+        history.visit(a, b);
+        history.visit(b, c);
+
+        assert_eq!(history.back(c).as_deref(), Some(b));
+        assert_eq!(history.back(b).as_deref(), Some(a));
+        assert_eq!(history.back(a), None);
+        assert_eq!(history.forward(a).as_deref(), Some(b));
+
+        // A new visit drops the forward stack
+        history.visit(b, a);
+        assert_eq!(history.forward(a), None);
+        assert_eq!(history.back(a).as_deref(), Some(b));
+    }
+
+    #[test]
+    fn test_recent_is_deduplicated() {
+        let (a, b) = (Path::new("/a"), Path::new("/b"));
+        let mut history = History::default();
+        history.visit(a, b);
+        history.visit(b, a);
+        assert_eq!(history.recent(), &[a.to_path_buf(), b.to_path_buf()]);
+
+        let target = history.back(a).unwrap();
+        history.discard(&target, true);
+        assert_eq!(history.recent(), &[a.to_path_buf()]);
+        assert_eq!(history.forward(a), None);
+    }
+}
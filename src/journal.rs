@@ -0,0 +1,559 @@
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::rename::rename_batch;
+use crate::transfer::{TransferItem, TransferKind};
+use crate::trash::{self, TrashItem};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Maximum number of operations kept on the undo stack
+const MAX_JOURNAL_ENTRIES: usize = 100;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Kind and items of a transfer a step runs in the background
+type Transfer = (TransferKind, Vec<TransferItem>);
+
+/// A file operation that changed the disk and can be reverted.
+#[derive(Debug, Clone)]
+pub enum Operation {
+    /// Items renamed in place, as (old, new) pairs.
+    Rename(Vec<(PathBuf, PathBuf)>),
+    /// Items moved by cut/paste, as (source, destination) pairs.
+    Move(Vec<(PathBuf, PathBuf)>),
+    /// Copies created by a paste, as (source, copy) pairs.
+    Copy(Vec<(PathBuf, PathBuf)>),
+    /// A newly created empty file or folder.
+    Create { path: PathBuf, is_dir: bool },
+    /// Items moved to the trash.
+    Trash(Vec<TrashItem>),
+    /// Operations performed together, undone in reverse order.
+    Batch(Vec<Operation>),
+}
+
+/// Undo/redo history of file operations.
+#[derive(Default)]
+pub struct Journal {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+    /// Undo or redo waiting for a transfer to finish
+    running: Option<Running>,
+}
+
+/// What an undo or redo needs next.
+pub enum Step {
+    /// The undo or redo is over: its description, or why it failed.
+    Done(io::Result<String>),
+    /// Items to move or copy in the background. The result goes to
+    /// `Journal::resume`.
+    Transfer(TransferKind, Vec<TransferItem>),
+}
+
+/// An undo or redo in progress.
+struct Running {
+    undo: bool,
+    op: Operation,
+    description: String,
+    /// Parts of `op` still to be performed, in order
+    parts: VecDeque<Operation>,
+    /// Operations reverting the parts already performed
+    inverses: Vec<Operation>,
+}
+
+// =============================================================================
+// Journal Implementation
+// =============================================================================
+
+impl Journal {
+    /// Records a freshly performed operation. Clears the redo history.
+    pub fn record(&mut self, op: Operation) {
+        self.undo.push(op);
+        if self.undo.len() > MAX_JOURNAL_ENTRIES {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn peek_undo(&self) -> Option<&Operation> {
+        self.undo.last()
+    }
+
+    pub fn peek_redo(&self) -> Option<&Operation> {
+        self.redo.last()
+    }
+
+    /// True while an undo or redo waits for a transfer.
+    pub fn is_busy(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Reverts the most recent operation and moves it to the redo stack.
+    /// On failure the operation stays on the undo stack so it can be retried.
+    pub fn undo(&mut self) -> Option<Step> {
+        if self.is_busy() {
+            return Some(Step::Done(Err(still_running())));
+        }
+        let op = self.undo.pop()?;
+        Some(self.start(true, op))
+    }
+
+    /// Re-applies the most recently undone operation.
+    /// On failure the operation stays on the redo stack so it can be retried.
+    pub fn redo(&mut self) -> Option<Step> {
+        if self.is_busy() {
+            return Some(Step::Done(Err(still_running())));
+        }
+        let op = self.redo.pop()?;
+        Some(self.start(false, op))
+    }
+
+    /// Continues the undo or redo once the transfer it asked for is over.
+    pub fn resume(&mut self, transferred: io::Result<()>) -> Step {
+        let Some(running) = self.running.take() else {
+            return Step::Done(Err(io::Error::other("no undo or redo is running")));
+        };
+        match transferred {
+            Ok(()) => self.advance(running),
+            Err(e) => self.fail(running, e),
+        }
+    }
+
+    fn start(&mut self, undo: bool, op: Operation) -> Step {
+        let mut parts = VecDeque::new();
+        op.split(undo, &mut parts);
+        let running = Running {
+            undo,
+            description: if undo {
+                op.describe_undo()
+            } else {
+                op.describe_redo()
+            },
+            op,
+            parts,
+            inverses: Vec::new(),
+        };
+        self.advance(running)
+    }
+
+    /// Performs parts until one needs a transfer or all are done.
+    fn advance(&mut self, mut running: Running) -> Step {
+        while let Some(part) = running.parts.pop_front() {
+            match part.perform(running.undo) {
+                Ok((inverse, transfer)) => {
+                    running.inverses.push(inverse);
+                    if let Some((kind, items)) = transfer.filter(|(_, items)| !items.is_empty()) {
+                        self.running = Some(running);
+                        return Step::Transfer(kind, items);
+                    }
+                }
+                Err(e) => return self.fail(running, e),
+            }
+        }
+
+        let Running {
+            undo,
+            op,
+            description,
+            mut inverses,
+            ..
+        } = running;
+        let inverse = match op {
+            Operation::Batch(_) => {
+                // Undone parts ran last to first
+                if undo {
+                    inverses.reverse();
+                }
+                Operation::Batch(inverses)
+            }
+            _ => inverses.pop().unwrap_or(op),
+        };
+        if undo {
+            self.redo.push(inverse);
+        } else {
+            self.undo.push(inverse);
+        }
+        Step::Done(Ok(description))
+    }
+
+    fn fail(&mut self, running: Running, e: io::Error) -> Step {
+        if running.undo {
+            self.undo.push(running.op);
+        } else {
+            self.redo.push(running.op);
+        }
+        Step::Done(Err(e))
+    }
+}
+
+// =============================================================================
+// Operation Implementation
+// =============================================================================
+
+impl Operation {
+    /// Describes what undoing this operation will do.
+    pub fn describe_undo(&self) -> String {
+        match self {
+            Operation::Rename(pairs) => match pairs.as_slice() {
+                [(from, to)] => format!(
+                    "rename '{}' back to '{}'",
+                    display_name(to),
+                    display_name(from)
+                ),
+                _ => format!("revert rename of {} items", pairs.len()),
+            },
+            Operation::Move(pairs) => match pairs.as_slice() {
+                [(from, to)] => format!(
+                    "move '{}' back to {}",
+                    display_name(to),
+                    display_parent(from)
+                ),
+                _ => format!("move {} items back", pairs.len()),
+            },
+            Operation::Copy(pairs) => match pairs.as_slice() {
+                [(_, copy)] => format!("remove copy '{}' (to trash)", display_name(copy)),
+                _ => format!("remove {} copies (to trash)", pairs.len()),
+            },
+            Operation::Create { path, is_dir } => format!(
+                "remove new {} '{}' (to trash)",
+                if *is_dir { "folder" } else { "file" },
+                display_name(path)
+            ),
+            Operation::Trash(items) => match items.as_slice() {
+                [item] => format!("restore '{}' from trash", item.name()),
+                _ => format!("restore {} items from trash", items.len()),
+            },
+            Operation::Batch(ops) => ops
+                .iter()
+                .rev()
+                .map(Operation::describe_undo)
+                .collect::<Vec<_>>()
+                .join(", then "),
+        }
+    }
+
+    /// Describes what redoing this operation will do.
+    pub fn describe_redo(&self) -> String {
+        match self {
+            Operation::Rename(pairs) => match pairs.as_slice() {
+                [(from, to)] => format!(
+                    "rename '{}' to '{}'",
+                    display_name(from),
+                    display_name(to)
+                ),
+                _ => format!("rename {} items again", pairs.len()),
+            },
+            Operation::Move(pairs) => match pairs.as_slice() {
+                [(from, to)] => {
+                    format!("move '{}' to {}", display_name(from), display_parent(to))
+                }
+                _ => format!("move {} items again", pairs.len()),
+            },
+            Operation::Copy(pairs) => match pairs.as_slice() {
+                [(src, _)] => format!("copy '{}' again", display_name(src)),
+                _ => format!("copy {} items again", pairs.len()),
+            },
+            Operation::Create { path, is_dir } => format!(
+                "create {} '{}' again",
+                if *is_dir { "folder" } else { "file" },
+                display_name(path)
+            ),
+            Operation::Trash(items) => match items.as_slice() {
+                [item] => format!("move '{}' to trash", item.name()),
+                _ => format!("move {} items to trash", items.len()),
+            },
+            Operation::Batch(ops) => ops
+                .iter()
+                .map(Operation::describe_redo)
+                .collect::<Vec<_>>()
+                .join(", then "),
+        }
+    }
+
+    /// Appends the parts this operation consists of, in the order an undo
+    /// (`undo`) or redo performs them.
+    fn split(&self, undo: bool, parts: &mut VecDeque<Operation>) {
+        match self {
+            Operation::Batch(ops) if undo => ops.iter().rev().for_each(|op| op.split(undo, parts)),
+            Operation::Batch(ops) => ops.iter().for_each(|op| op.split(undo, parts)),
+            _ => parts.push_back(self.clone()),
+        }
+    }
+
+    /// Undoes (`undo`) or redoes a single part. Returns the operation that
+    /// reverts it again, and the items still to move or copy in the
+    /// background. Items already in the wanted state are skipped, so a
+    /// partially failed step can simply be retried.
+    fn perform(&self, undo: bool) -> io::Result<(Operation, Option<Transfer>)> {
+        let transfer = match (self, undo) {
+            (Operation::Rename(pairs), true) => {
+                let reversed: Vec<(PathBuf, PathBuf)> =
+                    pairs.iter().map(|(a, b)| (b.clone(), a.clone())).collect();
+                rename_batch(&reversed)?;
+                None
+            }
+            (Operation::Rename(pairs), false) => {
+                rename_batch(pairs)?;
+                None
+            }
+            (Operation::Move(pairs), _) => {
+                let items = pairs
+                    .iter()
+                    .map(|(src, dest)| if undo { (dest, src) } else { (src, dest) })
+                    // Skip items that already have been moved
+                    .filter(|(from, to)| exists(from) || !exists(to))
+                    .map(|(from, to)| TransferItem {
+                        src: from.clone(),
+                        dest: to.clone(),
+                        overwrite: false,
+                    })
+                    .collect();
+                Some((TransferKind::Move, items))
+            }
+            (Operation::Copy(pairs), true) => {
+                for (_, copy) in pairs {
+                    if exists(copy) {
+                        trash::move_to_trash(copy)?;
+                    }
+                }
+                None
+            }
+            (Operation::Copy(pairs), false) => {
+                let items = pairs
+                    .iter()
+                    .filter(|(_, copy)| !exists(copy))
+                    .map(|(src, copy)| TransferItem {
+                        src: src.clone(),
+                        dest: copy.clone(),
+                        overwrite: false,
+                    })
+                    .collect();
+                Some((TransferKind::Copy, items))
+            }
+            (Operation::Create { path, .. }, true) => {
+                if exists(path) {
+                    trash::move_to_trash(path)?;
+                }
+                None
+            }
+            (Operation::Create { path, is_dir }, false) => {
+                if !exists(path) {
+                    if *is_dir {
+                        fs::create_dir(path)?;
+                    } else {
+                        fs::File::create(path)?;
+                    }
+                }
+                None
+            }
+            (Operation::Trash(items), true) => {
+                for item in items {
+                    if exists(&item.trashed_path) || !exists(&item.original_path) {
+                        trash::restore(item)?;
+                    }
+                }
+                None
+            }
+            (Operation::Trash(items), false) => {
+                let mut trashed = Vec::with_capacity(items.len());
+                for item in items {
+                    if exists(&item.original_path) {
+                        trashed.push(trash::move_to_trash(&item.original_path)?);
+                    }
+                }
+                return Ok((Operation::Trash(trashed), None));
+            }
+            (Operation::Batch(_), _) => unreachable!("batches are split into parts"),
+        };
+        Ok((self.clone(), transfer))
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+fn still_running() -> io::Error {
+    io::Error::other("the previous undo or redo is still running")
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+fn display_parent(path: &Path) -> String {
+    path.parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Message;
+    use crate::test_util::temp_dir;
+    use crate::transfer::{CopyPolicy, TransferEngine};
+    use std::{sync::mpsc, time::Duration};
+
+    /// Runs an undo or redo to the end, waiting for its transfers.
+    fn finish(journal: &mut Journal, step: Option<Step>) -> io::Result<String> {
+        let (sender, outcomes) = mpsc::channel();
+        let engine = TransferEngine::new(sender);
+        let mut step = step.expect("nothing to undo or redo");
+        loop {
+            match step {
+                Step::Done(result) => return result,
+                Step::Transfer(kind, items) => {
+                    assert!(journal.is_busy());
+                    engine.submit(kind, items, CopyPolicy::default());
+                    let Ok(Message::TransferFinished(outcome)) =
+                        outcomes.recv_timeout(Duration::from_secs(5))
+                    else {
+                        panic!("transfer did not finish");
+                    };
+                    step = journal.resume(outcome.check());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_rename() {
+        let dir = temp_dir("journal-rename");
+        let old = dir.join("a.txt");
+        let new = dir.join("b.txt");
+        fs::write(&old, b"data").unwrap();
+        fs::rename(&old, &new).unwrap();
+
+        let mut journal = Journal::default();
+        journal.record(Operation::Rename(vec![(old.clone(), new.clone())]));
+        assert_eq!(
+            journal.peek_undo().unwrap().describe_undo(),
+            "rename 'b.txt' back to 'a.txt'"
+        );
+
+        let step = journal.undo();
+        assert!(finish(&mut journal, step).is_ok());
+        assert!(old.exists() && !new.exists());
+        assert!(journal.peek_undo().is_none());
+
+        let step = journal.redo();
+        assert!(finish(&mut journal, step).is_ok());
+        assert!(!old.exists() && new.exists());
+        assert!(journal.peek_redo().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_undo_stays_on_stack() {
+        let dir = temp_dir("journal-conflict");
+        let old = dir.join("a.txt");
+        let new = dir.join("b.txt");
+        fs::write(&old, b"").unwrap();
+        fs::write(&new, b"").unwrap();
+
+        let mut journal = Journal::default();
+        journal.record(Operation::Move(vec![(old.clone(), new.clone())]));
+        let step = journal.undo();
+        assert!(finish(&mut journal, step).is_err());
+        assert!(!journal.is_busy());
+        assert!(journal.peek_undo().is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_undo_redo_move_across_filesystems() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = temp_dir("journal-xdev");
+        let other = Path::new("/dev/shm").join(format!("fylins-journal-{}", std::process::id()));
+        let device = |path: &Path| fs::metadata(path).map(|m| m.dev()).ok();
+        // Needs a second filesystem to move to
+        if fs::create_dir_all(&other).is_err() || device(&dir) == device(&other) {
+            let _ = fs::remove_dir_all(&other);
+            return;
+        }
+        let moved = dir.join("a.txt");
+        let src = other.join("a.txt");
+        fs::write(&moved, b"data").unwrap();
+
+        let mut journal = Journal::default();
+        journal.record(Operation::Move(vec![(src.clone(), moved.clone())]));
+        let step = journal.undo();
+        assert!(finish(&mut journal, step).is_ok());
+        assert_eq!(fs::read(&src).unwrap(), b"data");
+        assert!(!moved.exists());
+
+        let step = journal.redo();
+        assert!(finish(&mut journal, step).is_ok());
+        assert_eq!(fs::read(&moved).unwrap(), b"data");
+        assert!(!src.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&other).unwrap();
+    }
+
+    #[test]
+    fn test_batch_waits_for_its_move() {
+        let dir = temp_dir("journal-batch");
+        fs::write(dir.join("b"), b"moved").unwrap();
+        fs::write(dir.join("d"), b"renamed").unwrap();
+
+        let mut journal = Journal::default();
+        journal.record(Operation::Batch(vec![
+            Operation::Move(vec![(dir.join("a"), dir.join("b"))]),
+            Operation::Rename(vec![(dir.join("c"), dir.join("d"))]),
+        ]));
+
+        // The rename is reverted at once; the move goes to the transfer worker
+        let Some(Step::Transfer(TransferKind::Move, items)) = journal.undo() else {
+            panic!("move not handed to the worker");
+        };
+        assert!(dir.join("c").exists());
+        assert_eq!((&items[0].src, &items[0].dest), (&dir.join("b"), &dir.join("a")));
+        assert!(matches!(journal.redo(), Some(Step::Done(Err(_)))));
+        fs::rename(dir.join("b"), dir.join("a")).unwrap();
+        assert!(matches!(journal.resume(Ok(())), Step::Done(Ok(_))));
+        assert_eq!(
+            journal.peek_redo().unwrap().describe_redo(),
+            format!("move 'a' to {}, then rename 'c' to 'd'", dir.display())
+        );
+
+        let step = journal.redo();
+        assert!(finish(&mut journal, step).is_ok());
+        assert_eq!(fs::read(dir.join("b")).unwrap(), b"moved");
+        assert!(dir.join("d").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut journal = Journal::default();
+        journal.redo.push(Operation::Rename(Vec::new()));
+        journal.record(Operation::Rename(Vec::new()));
+        assert!(journal.peek_redo().is_none());
+        assert!(journal.undo().is_some());
+        assert!(journal.redo().is_some());
+    }
+}
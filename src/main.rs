@@ -1,6 +1,27 @@
 mod app;
+mod bookmarks;
+mod completion;
+mod config;
+mod events;
+mod filter;
+mod finder;
+mod frecency;
+mod fuzzy;
+mod grep;
 mod highlight;
+mod history;
+mod journal;
+mod paste;
+mod preview;
+mod rename;
+mod sort;
+#[cfg(test)]
+mod test_util;
+mod transfer;
+mod trash;
 mod ui;
+mod watcher;
+mod worker;
 
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
@@ -12,11 +33,18 @@ use std::{
     env,
     io::{self, stdout},
     path::PathBuf,
+    time::Duration,
 };
 
 use app::{App, Mode};
+use events::{Events, Message};
+use paste::ConflictChoice;
+use transfer::TransferKind;
 use ui::draw_ui;
 
+/// How long to wait for input before redrawing (keeps transfer progress live)
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 // =============================================================================
 // Terminal Setup/Cleanup
 // =============================================================================
@@ -29,6 +57,16 @@ fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
     Terminal::new(backend)
 }
 
+fn resume_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()
+}
+
 fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
     disable_raw_mode()?;
     execute!(
@@ -107,7 +145,12 @@ fn handle_normal_mode(app: &mut App, key: event::KeyEvent) -> bool {
     app.message = None;
 
     match key.code {
-        KeyCode::Char('q') | KeyCode::Esc => return false,
+        KeyCode::Esc if !app.selection.is_empty() => app.clear_selection(),
+        KeyCode::Esc if !app.filter.is_empty() => app.clear_filter(),
+        KeyCode::Esc if !app.attribute_filters.is_empty() => app.clear_attribute_filters(),
+        KeyCode::Char('q') | KeyCode::Esc => return !app.request_quit(),
+        KeyCode::Left if key.modifiers.contains(event::KeyModifiers::ALT) => app.go_back(),
+        KeyCode::Right if key.modifiers.contains(event::KeyModifiers::ALT) => app.go_forward(),
         KeyCode::Up | KeyCode::Char('k') => app.move_up(),
         KeyCode::Down | KeyCode::Char('j') => app.move_down(),
         KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
@@ -118,18 +161,53 @@ fn handle_normal_mode(app: &mut App, key: event::KeyEvent) -> bool {
         KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
             app.go_to_parent();
         }
+        KeyCode::Char('[') => app.go_back(),
+        KeyCode::Char(']') => app.go_forward(),
+        KeyCode::Char('g') => app.open_recent(),
+        KeyCode::Char('m') => app.start_set_bookmark(),
+        KeyCode::Char('\'') => app.start_jump_bookmark(),
+        KeyCode::Char('B') => app.open_bookmarks(),
         KeyCode::PageUp => app.scroll_preview_up(),
         KeyCode::PageDown => app.scroll_preview_down(),
         KeyCode::Char('/') => app.start_search(),
         KeyCode::Char('H') => app.toggle_hidden(),
+        KeyCode::Char('S') => app.start_sort(),
         KeyCode::Char('y') => app.yank_path(),
         KeyCode::Char('r') => app.start_rename(),
+        KeyCode::Char('E') => app.start_bulk_rename(),
+        KeyCode::Char('R') => app.start_pattern_rename(),
         KeyCode::Char('d') => app.start_delete(),
+        KeyCode::Char('D') => app.start_permanent_delete(),
+        KeyCode::Char('T') => app.open_trash(),
+        KeyCode::Char('u') => app.start_undo(),
+        KeyCode::Char('U') => app.start_redo(),
+        KeyCode::Char('X') => app.cancel_transfer(),
+        KeyCode::Char('P') => app.toggle_transfer_pause(),
         KeyCode::Char('o') => app.open_with_default(),
-        KeyCode::Char('p') => app.start_path(),
+        KeyCode::Char('p') => app.start_jump(),
+        KeyCode::Char('f') => app.start_find(),
+        KeyCode::Char('G') => app.start_grep(),
+        KeyCode::Char('t') => app.new_tab(),
+        KeyCode::Char('w') => app.close_tab(),
+        KeyCode::Char('s') => app.toggle_dual_pane(),
+        KeyCode::Char('M') => app.toggle_miller_columns(),
+        KeyCode::Tab if app.other_pane().is_some() => app.switch_pane(),
+        KeyCode::Tab => app.next_tab(),
+        KeyCode::BackTab => app.prev_tab(),
+        KeyCode::Char(c @ '1'..='9') => app.switch_tab(c as usize - '1' as usize),
+        KeyCode::Char('c') | KeyCode::F(5) if app.other_pane().is_some() => {
+            app.transfer_to_other_pane(TransferKind::Copy)
+        }
+        KeyCode::Char('x') | KeyCode::F(6) if app.other_pane().is_some() => {
+            app.transfer_to_other_pane(TransferKind::Move)
+        }
         KeyCode::Char('c') => app.copy_file(),
         KeyCode::Char('x') => app.cut_file(),
         KeyCode::Char('v') => app.paste_file(),
+        KeyCode::Char('C') => app.open_copy_options(),
+        KeyCode::Char(' ') => app.toggle_selection(),
+        KeyCode::Char('a') => app.select_all(),
+        KeyCode::Char('i') => app.invert_selection(),
         KeyCode::Char('n') => app.start_new_file(),
         KeyCode::Char('N') => app.start_new_folder(),
         KeyCode::Char('`') => app.go_to_start(),
@@ -144,6 +222,7 @@ fn handle_search_mode(app: &mut App, key: event::KeyEvent) -> bool {
         KeyCode::Esc => app.cancel_search(),
         KeyCode::Enter => app.confirm_search(),
         KeyCode::Backspace => app.backspace_search(),
+        KeyCode::Tab => app.cycle_filter_mode(),
         KeyCode::Up | KeyCode::Char('k')
             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
         {
@@ -174,6 +253,31 @@ fn handle_rename_mode(app: &mut App, key: event::KeyEvent) -> bool {
     true
 }
 
+fn handle_pattern_rename_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::PageUp => app.scroll_preview_up(),
+        KeyCode::PageDown => app.scroll_preview_down(),
+        _ => match handle_text_input(app, &key) {
+            InputAction::Cancel => app.cancel_pattern_rename(),
+            InputAction::Confirm => app.confirm_pattern_rename(),
+            InputAction::Handled => app.update_pattern_preview(),
+            InputAction::Unhandled => {}
+        },
+    }
+    true
+}
+
+fn handle_confirm_bulk_rename_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_bulk_rename(),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.cancel_bulk_rename(),
+        KeyCode::PageUp => app.scroll_preview_up(),
+        KeyCode::PageDown => app.scroll_preview_down(),
+        _ => {}
+    }
+    true
+}
+
 fn handle_confirm_delete_mode(app: &mut App, key: event::KeyEvent) -> bool {
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_delete(),
@@ -183,15 +287,194 @@ fn handle_confirm_delete_mode(app: &mut App, key: event::KeyEvent) -> bool {
     true
 }
 
-fn handle_path_mode(app: &mut App, key: event::KeyEvent) -> bool {
+fn handle_confirm_undo_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_undo(),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.cancel_journal_step(),
+        _ => {}
+    }
+    true
+}
+
+fn handle_confirm_redo_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_redo(),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.cancel_journal_step(),
+        _ => {}
+    }
+    true
+}
+
+fn handle_confirm_permanent_delete_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    // Only an explicit uppercase Y confirms a permanent delete
+    match key.code {
+        KeyCode::Char('Y') => app.confirm_permanent_delete(),
+        _ => app.cancel_delete(),
+    }
+    true
+}
+
+fn handle_recent_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('g') => app.close_recent(),
+        KeyCode::Up | KeyCode::Char('k') => app.recent_move_up(),
+        KeyCode::Down | KeyCode::Char('j') => app.recent_move_down(),
+        KeyCode::Enter | KeyCode::Char('l') => app.jump_to_recent(),
+        _ => {}
+    }
+    true
+}
+
+fn handle_sort_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) => app.apply_sort_key(c),
+        _ => app.cancel_sort(),
+    }
+    true
+}
+
+fn handle_bookmark_set_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) => app.set_bookmark(c),
+        _ => app.cancel_bookmark_key(),
+    }
+    true
+}
+
+fn handle_bookmark_jump_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) => app.jump_to_bookmark(c),
+        _ => app.cancel_bookmark_key(),
+    }
+    true
+}
+
+fn handle_bookmarks_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('B') => app.close_bookmarks(),
+        KeyCode::Up | KeyCode::Char('k') => app.bookmark_move_up(),
+        KeyCode::Down | KeyCode::Char('j') => app.bookmark_move_down(),
+        KeyCode::Enter | KeyCode::Char('l') => app.jump_to_selected_bookmark(),
+        KeyCode::Char('d') | KeyCode::Delete => app.delete_selected_bookmark(),
+        _ => {}
+    }
+    true
+}
+
+fn handle_trash_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    app.message = None;
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => app.close_trash(),
+        KeyCode::Up | KeyCode::Char('k') => app.trash_move_up(),
+        KeyCode::Down | KeyCode::Char('j') => app.trash_move_down(),
+        KeyCode::Enter | KeyCode::Char('r') => app.restore_trash_item(),
+        KeyCode::Char('D') => app.start_purge(),
+        _ => {}
+    }
+    true
+}
+
+fn handle_confirm_purge_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('Y') => app.confirm_purge(),
+        _ => app.cancel_purge(),
+    }
+    true
+}
+
+fn handle_conflict_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('o') => app.resolve_conflict(ConflictChoice::Overwrite),
+        KeyCode::Char('s') => app.resolve_conflict(ConflictChoice::Skip),
+        KeyCode::Char('n') => app.resolve_conflict(ConflictChoice::KeepNewer),
+        KeyCode::Char('a') => app.resolve_conflict(ConflictChoice::AutoSuffix),
+        KeyCode::Char('r') => app.start_conflict_rename(),
+        KeyCode::Char(' ') => app.toggle_conflict_apply_to_all(),
+        KeyCode::Esc | KeyCode::Char('q') => app.cancel_paste(),
+        _ => {}
+    }
+    true
+}
+
+fn handle_conflict_rename_mode(app: &mut App, key: event::KeyEvent) -> bool {
     match handle_text_input(app, &key) {
-        InputAction::Cancel => app.cancel_path(),
-        InputAction::Confirm => app.confirm_path(),
+        InputAction::Cancel => app.cancel_conflict_rename(),
+        InputAction::Confirm => app.confirm_conflict_rename(),
         InputAction::Handled | InputAction::Unhandled => {}
     }
     true
 }
 
+fn handle_copy_options_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('t') => app.toggle_preserve_timestamps(),
+        KeyCode::Char('m') => app.toggle_preserve_permissions(),
+        KeyCode::Char('l') => app.toggle_symlink_policy(),
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('C') => {
+            app.close_copy_options()
+        }
+        _ => {}
+    }
+    true
+}
+
+fn handle_jump_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Up => app.jump_move_up(),
+        KeyCode::Down => app.jump_move_down(),
+        KeyCode::Tab => app.start_path(),
+        _ => match handle_text_input(app, &key) {
+            InputAction::Cancel => app.cancel_jump(),
+            InputAction::Confirm => app.confirm_jump(),
+            InputAction::Handled => app.update_jump_matches(),
+            InputAction::Unhandled => {}
+        },
+    }
+    true
+}
+
+fn handle_find_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Up => app.find_move_up(),
+        KeyCode::Down => app.find_move_down(),
+        _ => match handle_text_input(app, &key) {
+            InputAction::Cancel => app.cancel_find(),
+            InputAction::Confirm => app.confirm_find(),
+            InputAction::Handled => app.update_find_results(),
+            InputAction::Unhandled => {}
+        },
+    }
+    true
+}
+
+fn handle_grep_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Up => app.grep_move_up(),
+        KeyCode::Down => app.grep_move_down(),
+        _ => match handle_text_input(app, &key) {
+            InputAction::Cancel => app.cancel_grep(),
+            InputAction::Confirm => app.confirm_grep(),
+            InputAction::Handled | InputAction::Unhandled => {}
+        },
+    }
+    true
+}
+
+fn handle_path_mode(app: &mut App, key: event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Up => app.path_move_up(),
+        KeyCode::Down => app.path_move_down(),
+        KeyCode::Tab => app.complete_path(),
+        _ => match handle_text_input(app, &key) {
+            InputAction::Cancel => app.cancel_path(),
+            InputAction::Confirm => app.confirm_path(),
+            InputAction::Handled => app.update_path_suggestions(),
+            InputAction::Unhandled => {}
+        },
+    }
+    true
+}
+
 fn handle_new_file_mode(app: &mut App, key: event::KeyEvent) -> bool {
     match handle_text_input(app, &key) {
         InputAction::Cancel => app.cancel_new(),
@@ -227,8 +510,26 @@ fn handle_key_event(app: &mut App, key: event::KeyEvent) -> bool {
         Mode::Normal => handle_normal_mode(app, key),
         Mode::Search => handle_search_mode(app, key),
         Mode::Rename => handle_rename_mode(app, key),
+        Mode::ConfirmBulkRename => handle_confirm_bulk_rename_mode(app, key),
+        Mode::PatternRename => handle_pattern_rename_mode(app, key),
         Mode::ConfirmDelete => handle_confirm_delete_mode(app, key),
+        Mode::ConfirmPermanentDelete => handle_confirm_permanent_delete_mode(app, key),
+        Mode::ConfirmUndo => handle_confirm_undo_mode(app, key),
+        Mode::ConfirmRedo => handle_confirm_redo_mode(app, key),
+        Mode::Recent => handle_recent_mode(app, key),
+        Mode::Sort => handle_sort_mode(app, key),
+        Mode::BookmarkSet => handle_bookmark_set_mode(app, key),
+        Mode::BookmarkJump => handle_bookmark_jump_mode(app, key),
+        Mode::Bookmarks => handle_bookmarks_mode(app, key),
+        Mode::Trash => handle_trash_mode(app, key),
+        Mode::ConfirmPurge => handle_confirm_purge_mode(app, key),
+        Mode::Conflict => handle_conflict_mode(app, key),
+        Mode::ConflictRename => handle_conflict_rename_mode(app, key),
+        Mode::CopyOptions => handle_copy_options_mode(app, key),
+        Mode::Jump => handle_jump_mode(app, key),
         Mode::Path => handle_path_mode(app, key),
+        Mode::Find => handle_find_mode(app, key),
+        Mode::Grep => handle_grep_mode(app, key),
         Mode::NewFile => handle_new_file_mode(app, key),
         Mode::NewFolder => handle_new_folder_mode(app, key),
         Mode::Help => handle_help_mode(app, key),
@@ -238,17 +539,37 @@ fn handle_key_event(app: &mut App, key: event::KeyEvent) -> bool {
 fn run_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
+    events: &Events,
 ) -> io::Result<()> {
     loop {
+        app.tick();
         terminal.draw(|f| draw_ui(f, app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if !handle_key_event(app, key) {
-                break;
+        // Redraw at least every tick so transfer progress shows
+        let Some(first) = events.next(TICK_RATE) else {
+            continue;
+        };
+        // Handle everything queued up before drawing again
+        for message in std::iter::once(first).chain(events.pending()) {
+            match message {
+                Message::Input(Event::Key(key)) => {
+                    if !handle_key_event(app, key) {
+                        return Ok(());
+                    }
+                }
+                message => app.handle_message(message),
             }
         }
+
+        // External programs need the terminal back while they run
+        if let Some(mut command) = app.take_editor_command() {
+            let _pause = events.pause_input();
+            restore_terminal(terminal)?;
+            let status = command.status();
+            resume_terminal(terminal)?;
+            app.finish_bulk_rename_edit(status);
+        }
     }
-    Ok(())
 }
 
 // =============================================================================
@@ -260,14 +581,20 @@ fn main() -> io::Result<()> {
         .nth(1)
         .map(PathBuf::from)
         .or_else(|| env::current_dir().ok())
-        .or_else(|| dirs_next::home_dir())
+        .or_else(dirs_next::home_dir)
         .unwrap_or_else(|| PathBuf::from("."));
 
     let mut terminal = setup_terminal()?;
-    let mut app = App::new(start_dir)?;
+    let events = Events::start();
+    let mut app = App::new(start_dir, events.sender())?;
+    app.start_workers();
 
-    let result = run_event_loop(&mut terminal, &mut app);
+    let result = run_event_loop(&mut terminal, &mut app, &events);
 
     restore_terminal(&mut terminal)?;
+    // Visits are saved in batches while browsing
+    if let Err(e) = app.frecency.flush() {
+        eprintln!("Cannot save jump database: {}", e);
+    }
     result
 }
@@ -0,0 +1,408 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::transfer::{TransferItem, TransferKind};
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// How to resolve a paste whose destination already exists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictChoice {
+    /// Replace the existing item (it is moved to the trash first).
+    Overwrite,
+    /// Leave the existing item alone and do not paste this one.
+    Skip,
+    /// Overwrite only if the pasted item is newer than the existing one.
+    KeepNewer,
+    /// Paste next to the existing item as `name_1.ext`, `name_2.ext`, ...
+    AutoSuffix,
+}
+
+/// Basic facts about one side of a conflict, for side-by-side comparison.
+#[derive(Debug, Clone)]
+pub struct FileInfo {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileInfo {
+    fn of(path: &Path) -> Self {
+        let meta = fs::metadata(path).ok();
+        FileInfo {
+            is_dir: meta.as_ref().map(|m| m.is_dir()).unwrap_or(false),
+            size: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+            modified: meta.and_then(|m| m.modified().ok()),
+        }
+    }
+}
+
+/// A pasted item whose destination name is already taken.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub src: PathBuf,
+    pub dest: PathBuf,
+    pub source: FileInfo,
+    pub existing: FileInfo,
+}
+
+impl Conflict {
+    fn new(src: PathBuf, dest: PathBuf) -> Self {
+        let source = FileInfo::of(&src);
+        let existing = FileInfo::of(&dest);
+        Conflict {
+            src,
+            dest,
+            source,
+            existing,
+        }
+    }
+
+    /// True if the pasted item was modified more recently than the existing one.
+    pub fn source_is_newer(&self) -> bool {
+        match (self.source.modified, self.existing.modified) {
+            (Some(src), Some(existing)) => src > existing,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A paste in progress: items are checked one by one, pausing whenever a
+/// conflict needs a decision from the user.
+pub struct PastePlan {
+    pub kind: TransferKind,
+    /// Directory the items are pasted into.
+    pub dir: PathBuf,
+    /// Items ready to be handed to the transfer worker.
+    pub ready: Vec<TransferItem>,
+    /// Items that could not be pasted, with the reason.
+    pub failed: Vec<(PathBuf, String)>,
+    pub skipped: usize,
+    /// Resolution applied to every further conflict, if chosen.
+    pub apply_to_all: bool,
+    /// Conflict currently waiting for a decision.
+    pub conflict: Option<Conflict>,
+    /// True when the items come from the clipboard, which a move then empties.
+    pub from_clipboard: bool,
+    remaining: VecDeque<PathBuf>,
+    last_choice: Option<ConflictChoice>,
+    reserved: HashSet<PathBuf>,
+}
+
+// =============================================================================
+// PastePlan Implementation
+// =============================================================================
+
+impl PastePlan {
+    pub fn new(kind: TransferKind, dir: PathBuf, sources: Vec<PathBuf>) -> Self {
+        PastePlan {
+            kind,
+            dir,
+            ready: Vec::new(),
+            failed: Vec::new(),
+            skipped: 0,
+            apply_to_all: false,
+            conflict: None,
+            from_clipboard: false,
+            remaining: sources.into(),
+            last_choice: None,
+            reserved: HashSet::new(),
+        }
+    }
+
+    /// Number of items not yet looked at (excluding the current conflict).
+    pub fn remaining(&self) -> usize {
+        self.remaining.len()
+    }
+
+    /// Processes items until the next conflict that needs a decision.
+    /// Returns true once every item has been handled.
+    pub fn advance(&mut self) -> bool {
+        while self.conflict.is_none() {
+            let Some(src) = self.remaining.pop_front() else {
+                return true;
+            };
+            let dest = match self.check_source(&src) {
+                Ok(dest) => dest,
+                Err(e) => {
+                    self.failed.push((src, e));
+                    continue;
+                }
+            };
+
+            if !self.is_taken(&dest) {
+                self.accept(src, dest, false);
+                continue;
+            }
+
+            let conflict = Conflict::new(src, dest);
+            match self.last_choice {
+                Some(choice) if self.apply_to_all => self.apply(conflict, choice),
+                _ => self.conflict = Some(conflict),
+            }
+        }
+        false
+    }
+
+    /// Resolves the current conflict with `choice`.
+    pub fn resolve(&mut self, choice: ConflictChoice) {
+        if let Some(conflict) = self.conflict.take() {
+            self.last_choice = Some(choice);
+            self.apply(conflict, choice);
+        }
+    }
+
+    /// Resolves the current conflict by pasting under a user-chosen name.
+    pub fn resolve_with_name(&mut self, name: &str) -> Result<(), String> {
+        let Some(conflict) = &self.conflict else {
+            return Ok(());
+        };
+        let dest = self.dir.join(name);
+        if self.is_taken(&dest) {
+            return Err(format!("'{}' already exists", name));
+        }
+        let src = conflict.src.clone();
+        self.conflict = None;
+        self.accept(src, dest, false);
+        Ok(())
+    }
+
+    /// Suggested name for pasting the current conflict under a new name.
+    pub fn suggested_name(&self) -> String {
+        self.conflict
+            .as_ref()
+            .map(|c| unique_destination(&self.dir, &c.src, &self.reserved))
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default()
+    }
+
+    fn apply(&mut self, conflict: Conflict, choice: ConflictChoice) {
+        let overwrite = match choice {
+            ConflictChoice::Overwrite => true,
+            ConflictChoice::KeepNewer => conflict.source_is_newer(),
+            ConflictChoice::Skip => false,
+            ConflictChoice::AutoSuffix => {
+                let dest = unique_destination(&self.dir, &conflict.src, &self.reserved);
+                self.accept(conflict.src, dest, false);
+                return;
+            }
+        };
+
+        if !overwrite {
+            self.skipped += 1;
+        } else if conflict.src == conflict.dest {
+            self.failed.push((
+                conflict.src,
+                "Cannot overwrite an item with itself".to_string(),
+            ));
+        } else if conflict.src.starts_with(&conflict.dest) {
+            self.failed.push((
+                conflict.src,
+                "Cannot overwrite a folder that contains the source".to_string(),
+            ));
+        } else if self.reserved.contains(&conflict.dest) {
+            self.failed.push((
+                conflict.src,
+                "Another pasted item already uses this name".to_string(),
+            ));
+        } else {
+            self.accept(conflict.src, conflict.dest, true);
+        }
+    }
+
+    fn accept(&mut self, src: PathBuf, dest: PathBuf, overwrite: bool) {
+        self.reserved.insert(dest.clone());
+        self.ready.push(TransferItem {
+            src,
+            dest,
+            overwrite,
+        });
+    }
+
+    fn is_taken(&self, dest: &Path) -> bool {
+        fs::symlink_metadata(dest).is_ok() || self.reserved.contains(dest)
+    }
+
+    /// Checks a single clipboard item and returns its natural destination.
+    fn check_source(&self, src: &Path) -> Result<PathBuf, String> {
+        if !src.exists() {
+            return Err("Source no longer exists".to_string());
+        }
+
+        if self.kind == TransferKind::Move && src.parent() == Some(self.dir.as_path()) {
+            return Err("Item is already in this directory".to_string());
+        }
+
+        let Some(file_name) = src.file_name() else {
+            return Err("Invalid source path".to_string());
+        };
+
+        let dest = self.dir.join(file_name);
+        if src.is_dir() && dest.starts_with(src) {
+            return Err("Cannot copy a directory into itself".to_string());
+        }
+
+        Ok(dest)
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Picks a destination for `src` inside `dir`, appending `_1`, `_2`, ...
+/// to the file stem while the name is taken on disk or `reserved`.
+pub fn unique_destination(dir: &Path, src: &Path, reserved: &HashSet<PathBuf>) -> PathBuf {
+    let taken = |p: &Path| p.exists() || reserved.contains(p);
+    let file_name = src.file_name().unwrap_or_default();
+    let mut dest = dir.join(file_name);
+    if !taken(&dest) {
+        return dest;
+    }
+
+    let stem = src
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = src
+        .extension()
+        .map(|s| format!(".{}", s.to_string_lossy()))
+        .unwrap_or_default();
+    let mut counter = 1;
+    while taken(&dest) {
+        dest = dir.join(format!("{}_{}{}", stem, counter, ext));
+        counter += 1;
+    }
+    dest
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_unique_destination() {
+        let dir = temp_dir("paste-unique");
+        let src = Path::new("/elsewhere/report.txt");
+        let mut reserved = HashSet::new();
+
+        assert_eq!(
+            unique_destination(&dir, src, &reserved),
+            dir.join("report.txt")
+        );
+
+        fs::write(dir.join("report.txt"), b"").unwrap();
+        fs::write(dir.join("report_1.txt"), b"").unwrap();
+        assert_eq!(
+            unique_destination(&dir, src, &reserved),
+            dir.join("report_2.txt")
+        );
+
+        reserved.insert(dir.join("report_2.txt"));
+        assert_eq!(
+            unique_destination(&dir, src, &reserved),
+            dir.join("report_3.txt")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_stops_at_conflict() {
+        let dir = temp_dir("paste-conflict");
+        let src_dir = dir.join("src");
+        let dest_dir = dir.join("dest");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(src_dir.join(name), b"new").unwrap();
+        }
+        fs::write(dest_dir.join("b.txt"), b"old").unwrap();
+        fs::write(dest_dir.join("c.txt"), b"old").unwrap();
+
+        let sources = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|n| src_dir.join(n))
+            .collect();
+        let mut plan = PastePlan::new(TransferKind::Copy, dest_dir.clone(), sources);
+
+        assert!(!plan.advance());
+        assert_eq!(plan.ready.len(), 1);
+        assert_eq!(plan.conflict.as_ref().unwrap().dest, dest_dir.join("b.txt"));
+
+        // Apply "auto-suffix" to this and every further conflict
+        plan.apply_to_all = true;
+        plan.resolve(ConflictChoice::AutoSuffix);
+        assert!(plan.advance());
+
+        let dests: Vec<PathBuf> = plan.ready.iter().map(|i| i.dest.clone()).collect();
+        assert_eq!(
+            dests,
+            vec![
+                dest_dir.join("a.txt"),
+                dest_dir.join("b_1.txt"),
+                dest_dir.join("c_1.txt")
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_overwrite_skip_and_rename() {
+        let dir = temp_dir("paste-resolve");
+        let src_dir = dir.join("src");
+        fs::create_dir_all(&src_dir).unwrap();
+        for name in ["a", "b", "c"] {
+            fs::write(src_dir.join(name), b"new").unwrap();
+            fs::write(dir.join(name), b"old").unwrap();
+        }
+
+        let sources = ["a", "b", "c"].iter().map(|n| src_dir.join(n)).collect();
+        let mut plan = PastePlan::new(TransferKind::Copy, dir.clone(), sources);
+
+        assert!(!plan.advance());
+        plan.resolve(ConflictChoice::Overwrite);
+        assert!(!plan.advance());
+        plan.resolve(ConflictChoice::Skip);
+        assert!(!plan.advance());
+        assert_eq!(plan.suggested_name(), "c_1");
+        assert!(plan.resolve_with_name("a").is_err());
+        assert!(plan.resolve_with_name("renamed").is_ok());
+        assert!(plan.advance());
+
+        assert_eq!(plan.skipped, 1);
+        assert_eq!(plan.ready.len(), 2);
+        assert!(plan.ready[0].overwrite);
+        assert_eq!(plan.ready[1].dest, dir.join("renamed"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_overwrite_self_is_refused() {
+        let dir = temp_dir("paste-self");
+        fs::write(dir.join("a"), b"").unwrap();
+
+        let mut plan = PastePlan::new(TransferKind::Copy, dir.clone(), vec![dir.join("a")]);
+        assert!(!plan.advance());
+        plan.resolve(ConflictChoice::Overwrite);
+        assert!(plan.advance());
+        assert!(plan.ready.is_empty());
+        assert_eq!(plan.failed.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, BufRead, BufReader, Read, Seek},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::Sender,
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::SystemTime,
+};
+
+use crate::app::{
+    is_text, list_directory_until, parse_bmp_dimensions, parse_gif_dimensions, parse_jpeg_dimensions,
+    parse_png_dimensions, Preview,
+};
+use crate::events::Message;
+use crate::highlight::highlight_code;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Maximum bytes to read for file preview
+const MAX_PREVIEW_BYTES: usize = 16 * 1024;
+
+/// Number of rendered previews kept for revisiting
+const CACHE_CAPACITY: usize = 64;
+
+/// File contents are read in pieces this size, checking for cancellation
+/// in between
+const READ_CHUNK_BYTES: usize = 4 * 1024;
+
+/// Lines shown above a content search match in the preview
+const GREP_CONTEXT_LINES: usize = 3;
+
+/// Lines of a file loaded into the preview for a content search match
+const GREP_PREVIEW_LINES: usize = 500;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Identifies a preview: a changed modification time or size means the
+/// file changed and needs loading again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewKey {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub modified: Option<SystemTime>,
+    pub size: u64,
+    /// Directory previews list hidden files only when they are shown
+    pub show_hidden: bool,
+    /// 1-based line of a content search match; the file is shown from a
+    /// few lines above it
+    pub line: Option<usize>,
+}
+
+/// Recently shown previews, most recently used first.
+#[derive(Default)]
+pub struct PreviewCache {
+    entries: VecDeque<(PreviewKey, Preview)>,
+}
+
+/// Loads previews on one long-lived thread. Only the latest request is
+/// kept, so moving quickly over many entries loads just the last one.
+pub struct PreviewWorker {
+    shared: Arc<WorkerShared>,
+}
+
+#[derive(Default)]
+struct WorkerShared {
+    /// Latest request the worker has not picked up yet
+    request: Mutex<Option<PreviewKey>>,
+    wakeup: Condvar,
+    /// Bumped by every request and cancel; a load started under an older
+    /// generation stops and is not posted
+    generation: AtomicU64,
+    shutdown: AtomicBool,
+}
+
+// =============================================================================
+// PreviewCache Implementation
+// =============================================================================
+
+impl PreviewCache {
+    pub fn get(&mut self, key: &PreviewKey) -> Option<Preview> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        let preview = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(preview)
+    }
+
+    /// Stores `preview`, evicting the least recently used one when full.
+    /// Errors and paths without a modification time are not kept, as there
+    /// is no telling when they change.
+    pub fn insert(&mut self, key: PreviewKey, preview: Preview) {
+        if key.modified.is_none() || matches!(preview, Preview::Error(_)) {
+            return;
+        }
+        self.entries.retain(|(k, _)| k.path != key.path);
+        self.entries.push_front((key, preview));
+        self.entries.truncate(CACHE_CAPACITY);
+    }
+
+    /// Forgets `path`, e.g. a directory whose contents changed.
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|(k, _)| k.path != path);
+    }
+}
+
+// =============================================================================
+// Loading
+// =============================================================================
+
+impl PreviewWorker {
+    /// Starts the worker, which posts loaded previews to `sender`.
+    pub fn start(sender: Sender<Message>) -> PreviewWorker {
+        let shared = Arc::new(WorkerShared::default());
+        let worker = Arc::clone(&shared);
+        thread::spawn(move || loop {
+            let (key, generation) = {
+                let mut request = worker.request.lock().unwrap_or_else(|e| e.into_inner());
+                loop {
+                    if worker.shutdown.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if let Some(key) = request.take() {
+                        break (key, worker.generation.load(Ordering::SeqCst));
+                    }
+                    request = worker.wakeup.wait(request).unwrap_or_else(|e| e.into_inner());
+                }
+            };
+            let cancelled = || worker.generation.load(Ordering::SeqCst) != generation;
+            if let Some(preview) = load_until(&key, &cancelled) {
+                if !cancelled() && sender.send(Message::Preview { key, preview }).is_err() {
+                    return;
+                }
+            }
+        });
+        PreviewWorker { shared }
+    }
+
+    /// Loads the preview for `key`, replacing any earlier request and
+    /// cancelling the load in progress.
+    pub fn request(&self, key: PreviewKey) {
+        let mut request = self.shared.request.lock().unwrap_or_else(|e| e.into_inner());
+        self.shared.generation.fetch_add(1, Ordering::SeqCst);
+        *request = Some(key);
+        self.shared.wakeup.notify_one();
+    }
+
+    /// Drops the pending request and stops the load in progress.
+    pub fn cancel(&self) {
+        let mut request = self.shared.request.lock().unwrap_or_else(|e| e.into_inner());
+        self.shared.generation.fetch_add(1, Ordering::SeqCst);
+        *request = None;
+    }
+}
+
+impl Drop for PreviewWorker {
+    fn drop(&mut self) {
+        self.cancel();
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.wakeup.notify_one();
+    }
+}
+
+/// Reads and renders the preview for `key`.
+pub fn load(key: &PreviewKey) -> Preview {
+    load_until(key, &|| false).unwrap_or(Preview::None)
+}
+
+/// Like `load`, but gives up and returns `None` once `cancelled` is true.
+fn load_until(key: &PreviewKey, cancelled: &dyn Fn() -> bool) -> Option<Preview> {
+    if key.is_dir {
+        load_directory_preview(&key.path, key.show_hidden, cancelled)
+    } else if let Some(line) = key.line {
+        load_match_preview(&key.path, line, cancelled)
+    } else {
+        load_file_preview(&key.path, cancelled)
+    }
+}
+
+/// Index of the first line shown in the preview of a match on `line`.
+pub fn match_window_start(line: usize) -> usize {
+    line.saturating_sub(1).saturating_sub(GREP_CONTEXT_LINES)
+}
+
+fn load_directory_preview(
+    path: &Path,
+    show_hidden: bool,
+    cancelled: &dyn Fn() -> bool,
+) -> Option<Preview> {
+    Some(match list_directory_until(path, show_hidden, cancelled) {
+        Ok(items) => {
+            let formatted: Vec<String> = items
+                .into_iter()
+                .map(|(is_dir, name)| {
+                    if is_dir {
+                        format!("📁 {}", name)
+                    } else {
+                        format!("📄 {}", name)
+                    }
+                })
+                .collect();
+
+            Preview::Directory(formatted)
+        }
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => return None,
+        Err(e) => Preview::Error(format!("Cannot read directory: {}", e)),
+    })
+}
+
+fn load_file_preview(path: &Path, cancelled: &dyn Fn() -> bool) -> Option<Preview> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    // Opening a FIFO or device could block the worker indefinitely
+    match fs::metadata(path) {
+        Ok(meta) if !meta.is_file() => {
+            return Some(Preview::Error("Not a regular file".to_string()));
+        }
+        Err(e) => return Some(Preview::Error(format!("Cannot open: {}", e))),
+        Ok(_) => {}
+    }
+
+    // Check for image files
+    if matches!(
+        extension.as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "webp"
+    ) {
+        return Some(load_image_preview(path, &extension));
+    }
+
+    let mut file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) => return Some(Preview::Error(format!("Cannot open: {}", e))),
+    };
+
+    let mut buffer = vec![0u8; MAX_PREVIEW_BYTES];
+    let mut bytes_read = 0;
+    while bytes_read < buffer.len() {
+        if cancelled() {
+            return None;
+        }
+        let end = (bytes_read + READ_CHUNK_BYTES).min(buffer.len());
+        match file.read(&mut buffer[bytes_read..end]) {
+            Ok(0) => break,
+            Ok(n) => bytes_read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Some(Preview::Error(format!("Cannot read: {}", e))),
+        }
+    }
+    buffer.truncate(bytes_read);
+
+    if !is_text(&buffer) {
+        return Some(Preview::Binary(buffer));
+    }
+    if cancelled() {
+        return None;
+    }
+    Some(match String::from_utf8(buffer) {
+        Ok(s) => Preview::Text {
+            lines: highlight_code(&s, &extension),
+            content: s,
+            extension,
+        },
+        Err(e) => Preview::Binary(e.into_bytes()),
+    })
+}
+
+/// Loads the lines around a content search match on `line`, reading no
+/// further into the file than the preview shows.
+fn load_match_preview(path: &Path, line: usize, cancelled: &dyn Fn() -> bool) -> Option<Preview> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    // Opening a FIFO or device could block the worker indefinitely
+    let file = match fs::metadata(path) {
+        Ok(meta) if !meta.is_file() => {
+            return Some(Preview::Error("Not a regular file".to_string()));
+        }
+        Ok(_) => fs::File::open(path),
+        Err(e) => Err(e),
+    };
+    let mut reader = match file {
+        Ok(f) => BufReader::new(f),
+        Err(e) => return Some(Preview::Error(format!("Cannot open: {}", e))),
+    };
+
+    let start = match_window_start(line);
+    let mut window = Vec::with_capacity(GREP_PREVIEW_LINES);
+    let mut buffer = Vec::new();
+    for index in 0..start + GREP_PREVIEW_LINES {
+        if cancelled() {
+            return None;
+        }
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => return Some(Preview::Error(format!("Cannot read: {}", e))),
+        }
+        if index >= start {
+            let text = String::from_utf8_lossy(&buffer);
+            window.push(text.trim_end_matches(['\n', '\r']).to_string());
+        }
+    }
+
+    let content = window.join("\n");
+    Some(Preview::Text {
+        lines: highlight_code(&content, &extension),
+        content,
+        extension,
+    })
+}
+
+fn load_image_preview(path: &Path, ext: &str) -> Preview {
+    let mut file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) => return Preview::Error(format!("Cannot open: {}", e)),
+    };
+
+    let mut header = [0u8; 32];
+    if file.read(&mut header).is_err() {
+        return Preview::Error("Cannot read image header".to_string());
+    }
+
+    let (width, height, format): (u32, u32, &'static str) = match ext {
+        "png" => parse_png_dimensions(&header),
+        "jpg" | "jpeg" => {
+            // JPEG requires reading more data
+            let mut full_header = vec![0u8; 512];
+            let _ = file.rewind();
+            let _ = file.read(&mut full_header);
+            parse_jpeg_dimensions(&full_header)
+        }
+        "gif" => parse_gif_dimensions(&header),
+        "bmp" => parse_bmp_dimensions(&header),
+        "ico" => (0, 0, "ICO"),
+        "webp" => (0, 0, "WEBP"),
+        _ => (0, 0, "Image"),
+    };
+
+    Preview::Image {
+        width,
+        height,
+        format,
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn key(name: &str, seconds: u64) -> PreviewKey {
+        PreviewKey {
+            path: PathBuf::from(name),
+            is_dir: false,
+            modified: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(seconds)),
+            size: 1,
+            show_hidden: false,
+            line: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = PreviewCache::default();
+        for i in 0..CACHE_CAPACITY {
+            cache.insert(key(&i.to_string(), 1), Preview::None);
+        }
+        // Using the oldest entry keeps it; the next oldest goes instead
+        assert!(cache.get(&key("0", 1)).is_some());
+        cache.insert(key("new", 1), Preview::None);
+        assert!(cache.get(&key("0", 1)).is_some());
+        assert!(cache.get(&key("1", 1)).is_none());
+
+        // A newer modification time misses and replaces the old preview
+        assert!(cache.get(&key("0", 2)).is_none());
+        cache.insert(key("0", 2), Preview::None);
+        assert!(cache.get(&key("0", 1)).is_none());
+        cache.remove(Path::new("0"));
+        assert!(cache.get(&key("0", 2)).is_none());
+    }
+
+    #[test]
+    fn test_worker_loads_latest_request() {
+        let dir = temp_dir("preview");
+        for name in ["a.txt", "b.txt"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        let file = |name: &str| PreviewKey {
+            path: dir.join(name),
+            ..key(name, 1)
+        };
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let worker = PreviewWorker::start(sender);
+        worker.request(file("a.txt"));
+        worker.request(file("b.txt"));
+        // "a.txt" may or may not have been loaded before it was replaced
+        let latest = loop {
+            let Ok(Message::Preview { key, preview }) = receiver.recv() else {
+                panic!("no preview loaded");
+            };
+            if key == file("b.txt") {
+                break preview;
+            }
+        };
+        assert!(matches!(latest, Preview::Text { content, .. } if content == "b.txt"));
+
+        // Cancelled loads stop without a result
+        assert!(load_until(&file("a.txt"), &|| true).is_none());
+        let listing = PreviewKey {
+            is_dir: true,
+            ..file("")
+        };
+        assert!(load_until(&listing, &|| true).is_none());
+        assert!(matches!(load(&listing), Preview::Directory(items) if items.len() == 2));
+
+        drop(worker);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_match_preview_starts_above_the_line() {
+        let dir = temp_dir("preview-match");
+        let text: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        fs::write(dir.join("a.txt"), text.join("\r\n")).unwrap();
+        let at = |line| PreviewKey {
+            path: dir.join("a.txt"),
+            line: Some(line),
+            ..key("a.txt", 1)
+        };
+
+        let Preview::Text { content, .. } = load(&at(10)) else {
+            panic!("no text preview");
+        };
+        assert_eq!(content.lines().next(), Some("line 7"));
+        assert_eq!(content.lines().count(), 14);
+        // A match on the first line, or a bogus line 0, starts at the top
+        for line in [1, 0] {
+            let Preview::Text { content, .. } = load(&at(line)) else {
+                panic!("no text preview");
+            };
+            assert!(content.starts_with("line 1\n"));
+        }
+        assert!(load_until(&at(10), &|| true).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
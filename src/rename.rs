@@ -0,0 +1,576 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap, HashSet},
+    env, fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use regex::{Captures, Regex};
+
+use crate::app::validate_filename;
+use crate::transfer::rename_no_replace;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Editor used when neither $VISUAL nor $EDITOR is set
+#[cfg(unix)]
+const FALLBACK_EDITOR: &str = "vi";
+#[cfg(windows)]
+const FALLBACK_EDITOR: &str = "notepad";
+
+/// Names tried for the temp file before giving up
+const TEMP_FILE_ATTEMPTS: usize = 16;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Renames inside one directory that are applied together.
+#[derive(Debug, Clone)]
+pub struct RenamePlan {
+    pub dir: PathBuf,
+    /// (old name, new name) pairs; only names that actually change.
+    pub renames: Vec<(String, String)>,
+}
+
+/// Find/replace rule for pattern renames, written as `find/replace[/options]`.
+///
+/// `find` is a regex; `replace` may use capture groups (`$1`, `${name}`)
+/// and a counter (`{n}`, or `{n:03}` zero-padded). Options are `upper`,
+/// `lower` and `title` (applied to the name without extension) and
+/// `ext=<new>` (an empty value removes the extension).
+#[derive(Debug)]
+pub struct RenamePattern {
+    find: Option<Regex>,
+    replace: Vec<Template>,
+    case: Option<CaseTransform>,
+    extension: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Template {
+    Text(String),
+    Counter { width: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CaseTransform {
+    Upper,
+    Lower,
+    Title,
+}
+
+/// A bulk rename whose names are being edited in an external editor.
+pub struct EditSession {
+    pub dir: PathBuf,
+    /// Temp file holding one name per line.
+    pub file: PathBuf,
+    names: Vec<String>,
+}
+
+// =============================================================================
+// RenamePlan Implementation
+// =============================================================================
+
+impl RenamePlan {
+    /// Builds a validated plan. Pairs whose name does not change are dropped.
+    pub fn new(dir: &Path, renames: Vec<(String, String)>) -> Result<RenamePlan, String> {
+        let renames: Vec<(String, String)> =
+            renames.into_iter().filter(|(old, new)| old != new).collect();
+        let problems = check_renames(dir, &renames);
+        if let Some(((_, new), problem)) = renames
+            .iter()
+            .zip(&problems)
+            .find_map(|(pair, p)| p.as_ref().map(|p| (pair, p)))
+        {
+            return Err(format!("'{}': {}", new, problem));
+        }
+        Ok(RenamePlan {
+            dir: dir.to_path_buf(),
+            renames,
+        })
+    }
+
+    /// Performs every rename, or none of them if one fails.
+    /// Returns the (old, new) paths for the journal.
+    pub fn execute(&self) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        let pairs: Vec<(PathBuf, PathBuf)> = self
+            .renames
+            .iter()
+            .map(|(old, new)| (self.dir.join(old), self.dir.join(new)))
+            .collect();
+        rename_batch(&pairs)?;
+        Ok(pairs)
+    }
+}
+
+/// Finds the problem, if any, with each (old, new) pair of a batch:
+/// invalid names, names used twice and collisions with items outside the batch.
+pub fn check_renames(dir: &Path, renames: &[(String, String)]) -> Vec<Option<String>> {
+    let sources: HashSet<&str> = renames.iter().map(|(old, _)| old.as_str()).collect();
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for (_, new) in renames {
+        *uses.entry(new.as_str()).or_default() += 1;
+    }
+
+    renames
+        .iter()
+        .map(|(old, new)| {
+            if let Err(msg) = validate_filename(new) {
+                return Some(msg);
+            }
+            if uses.get(new.as_str()).copied().unwrap_or(0) > 1 {
+                return Some("name used more than once".to_string());
+            }
+            if old != new && !sources.contains(new.as_str()) && exists(&dir.join(new)) {
+                return Some("already exists".to_string());
+            }
+            None
+        })
+        .collect()
+}
+
+// =============================================================================
+// RenamePattern Implementation
+// =============================================================================
+
+impl RenamePattern {
+    pub fn parse(input: &str) -> Result<RenamePattern, String> {
+        let mut parts = input.splitn(3, '/');
+        let find = parts.next().unwrap_or("");
+        let replace = parts.next();
+        let options = parts.next().unwrap_or("");
+
+        // Without a replacement the names stay as they are
+        let find = match replace {
+            Some(_) if !find.is_empty() => {
+                Some(Regex::new(find).map_err(|e| format!("Invalid pattern: {}", e))?)
+            }
+            _ => None,
+        };
+
+        let mut pattern = RenamePattern {
+            find,
+            replace: parse_template(replace.unwrap_or(""))?,
+            case: None,
+            extension: None,
+        };
+        for option in options.split([',', ' ']).filter(|o| !o.is_empty()) {
+            match option {
+                "upper" => pattern.case = Some(CaseTransform::Upper),
+                "lower" => pattern.case = Some(CaseTransform::Lower),
+                "title" => pattern.case = Some(CaseTransform::Title),
+                _ => match option.strip_prefix("ext=") {
+                    Some(ext) => pattern.extension = Some(ext.trim_start_matches('.').to_string()),
+                    None => return Err(format!("Unknown option '{}'", option)),
+                },
+            }
+        }
+        Ok(pattern)
+    }
+
+    /// True if this pattern renames `name`. Without a find pattern, every
+    /// name is affected by the options.
+    pub fn matches(&self, name: &str) -> bool {
+        self.find.as_ref().is_none_or(|find| find.is_match(name))
+    }
+
+    /// New name for `name`; `number` is what `{n}` expands to.
+    /// Names the pattern does not match are returned unchanged.
+    pub fn apply(&self, name: &str, number: usize) -> String {
+        let replaced = match &self.find {
+            Some(find) if !find.is_match(name) => return name.to_string(),
+            Some(find) => find
+                .replace_all(name, |caps: &Captures| self.expand(caps, number))
+                .into_owned(),
+            None => name.to_string(),
+        };
+
+        let (stem, ext) = split_extension(&replaced);
+        let stem = match self.case {
+            Some(CaseTransform::Upper) => stem.to_uppercase(),
+            Some(CaseTransform::Lower) => stem.to_lowercase(),
+            Some(CaseTransform::Title) => title_case(stem),
+            None => stem.to_string(),
+        };
+        match self.extension.as_deref().or(ext) {
+            Some("") | None => stem,
+            Some(ext) => format!("{}.{}", stem, ext),
+        }
+    }
+
+    fn expand(&self, caps: &Captures, counter: usize) -> String {
+        let mut out = String::new();
+        for part in &self.replace {
+            match part {
+                Template::Text(text) => caps.expand(text, &mut out),
+                Template::Counter { width } => {
+                    out.push_str(&format!("{:0width$}", counter, width = width))
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Splits a replacement into literal text and `{n}` / `{n:0W}` counters.
+fn parse_template(template: &str) -> Result<Vec<Template>, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{n") {
+        let end = rest[start..].find('}').map(|i| start + i);
+        let width = end.and_then(|end| match &rest[start + 2..end] {
+            "" => Some(Ok(0)),
+            spec => spec.strip_prefix(':').map(|digits| {
+                digits
+                    .parse()
+                    .map_err(|_| format!("Invalid counter '{{n{}}}'", spec))
+            }),
+        });
+        match (width, end) {
+            (Some(width), Some(end)) => {
+                text.push_str(&rest[..start]);
+                if !text.is_empty() {
+                    parts.push(Template::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Template::Counter { width: width? });
+                rest = &rest[end + 1..];
+            }
+            // Not a counter, such as "${name}"; keep it as text
+            _ => {
+                text.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(Template::Text(text));
+    }
+    Ok(parts)
+}
+
+/// Splits "name.ext" into ("name", Some("ext")). Dotfiles have no extension.
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    match name.rfind('.') {
+        Some(i) if i > 0 => (&name[..i], Some(&name[i + 1..])),
+        _ => (name, None),
+    }
+}
+
+/// Capitalizes the first letter of every word.
+fn title_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for c in text.chars() {
+        if at_word_start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        at_word_start = !c.is_alphanumeric();
+    }
+    out
+}
+
+// =============================================================================
+// EditSession Implementation
+// =============================================================================
+
+impl EditSession {
+    /// Writes `names` to a temp file, one per line, ready for editing.
+    pub fn create(dir: &Path, names: Vec<String>) -> Result<EditSession, String> {
+        if let Some(name) = names.iter().find(|n| n.contains('\n')) {
+            return Err(format!("Cannot edit '{}': name contains a newline", name.trim()));
+        }
+        let mut content = names.join("\n");
+        content.push('\n');
+        let file =
+            create_temp_file(&content).map_err(|e| format!("Cannot create temp file: {}", e))?;
+        Ok(EditSession {
+            dir: dir.to_path_buf(),
+            file,
+            names,
+        })
+    }
+
+    /// Command that opens the temp file in the user's editor.
+    pub fn editor_command(&self) -> Command {
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .ok()
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+        // Allow editors with arguments, such as "code --wait"
+        let mut parts = editor.split_whitespace();
+        let mut command = Command::new(parts.next().unwrap_or(FALLBACK_EDITOR));
+        command.args(parts).arg(&self.file);
+        command
+    }
+
+    /// Reads the edited names back and removes the temp file.
+    pub fn finish(self) -> Result<RenamePlan, String> {
+        let edited = fs::read_to_string(&self.file);
+        let _ = fs::remove_file(&self.file);
+        let edited = edited.map_err(|e| format!("Cannot read edited names: {}", e))?;
+        plan_from_lines(&self.dir, &self.names, &edited)
+    }
+
+    /// Abandons the session and removes the temp file.
+    pub fn discard(self) {
+        let _ = fs::remove_file(&self.file);
+    }
+}
+
+/// Writes `content` to a new file in the temp directory that only the
+/// current user can read. Never opens an existing path, so a link planted
+/// at a guessed name cannot redirect the write.
+fn create_temp_file(content: &str) -> io::Result<PathBuf> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut attempts = 0;
+    loop {
+        // RandomState is seeded randomly, which makes the name unpredictable
+        let suffix = RandomState::new().build_hasher().finish();
+        let path = env::temp_dir().join(format!("fylins-rename-{:016x}.txt", suffix));
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < TEMP_FILE_ATTEMPTS => {
+                attempts += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if let Err(e) = file.write_all(content.as_bytes()) {
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        }
+        return Ok(path);
+    }
+}
+
+/// Pairs each original name with the line at the same position.
+fn plan_from_lines(dir: &Path, names: &[String], edited: &str) -> Result<RenamePlan, String> {
+    let mut lines: Vec<&str> = edited.lines().map(|l| l.trim_end_matches('\r')).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    if lines.len() != names.len() {
+        return Err(format!(
+            "Expected {} names but found {} lines; nothing renamed",
+            names.len(),
+            lines.len()
+        ));
+    }
+    let renames = names
+        .iter()
+        .zip(lines)
+        .map(|(old, new)| (old.clone(), new.to_string()))
+        .collect();
+    RenamePlan::new(dir, renames)
+}
+
+// =============================================================================
+// Batch Renaming
+// =============================================================================
+
+/// Renames each (from, to) pair as one batch, so swaps and cycles work.
+/// Pairs that are already done are skipped. If any rename fails, the
+/// renames of this call are rolled back.
+pub fn rename_batch(pairs: &[(PathBuf, PathBuf)]) -> io::Result<()> {
+    let pending: Vec<&(PathBuf, PathBuf)> = pairs
+        .iter()
+        .filter(|(from, to)| from != to && (exists(from) || !exists(to)))
+        .collect();
+
+    let sources: HashSet<&PathBuf> = pending.iter().map(|(from, _)| from).collect();
+    if let Some((_, to)) = pending
+        .iter()
+        .find(|(_, to)| exists(to) && !sources.contains(to))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("'{}' already exists", to.display()),
+        ));
+    }
+
+    // Move every source aside first, so no target is still occupied
+    let mut staged: Vec<(&PathBuf, PathBuf)> = Vec::with_capacity(pending.len());
+    for (from, _) in &pending {
+        let temp = staging_path(from);
+        if let Err(e) = fs::rename(from, &temp) {
+            unstage(&staged);
+            return Err(e);
+        }
+        staged.push((from, temp));
+    }
+
+    // Another program may have taken a target since the check above
+    for (i, ((_, to), (_, temp))) in pending.iter().zip(&staged).enumerate() {
+        if let Err(e) = rename_no_replace(temp, to) {
+            for ((_, placed), (_, temp)) in pending[..i].iter().zip(&staged).rev() {
+                let _ = fs::rename(placed, temp);
+            }
+            unstage(&staged);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+fn unstage(staged: &[(&PathBuf, PathBuf)]) {
+    for (from, temp) in staged.iter().rev() {
+        let _ = fs::rename(temp, from);
+    }
+}
+
+/// Free temporary name next to `path`.
+fn staging_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = path.parent().unwrap_or(Path::new(""));
+    (0..)
+        .map(|i| dir.join(format!(".{}.fylins-rename-{}-{}", name, std::process::id(), i)))
+        .find(|p| !exists(p))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_plan_from_lines() {
+        let dir = temp_dir("rename-lines");
+        fs::write(dir.join("a.jpg"), b"").unwrap();
+        fs::write(dir.join("b.jpg"), b"").unwrap();
+        fs::write(dir.join("taken.jpg"), b"").unwrap();
+        let names = vec!["a.jpg".to_string(), "b.jpg".to_string()];
+
+        let plan = plan_from_lines(&dir, &names, "a.jpg\nbeach.jpg\n\n").unwrap();
+        assert_eq!(plan.renames, vec![("b.jpg".to_string(), "beach.jpg".to_string())]);
+
+        assert!(plan_from_lines(&dir, &names, "a.jpg\n").is_err());
+        assert!(plan_from_lines(&dir, &names, "x.jpg\nx.jpg\n").is_err());
+        assert!(plan_from_lines(&dir, &names, "a.jpg\ntaken.jpg\n").is_err());
+        assert!(plan_from_lines(&dir, &names, "a.jpg\nsub/b.jpg\n").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_edit_session_temp_file() {
+        let names = vec!["a.txt".to_string(), "b.txt".to_string()];
+        let first = EditSession::create(Path::new("."), names.clone()).unwrap();
+        let second = EditSession::create(Path::new("."), names).unwrap();
+        assert_ne!(first.file, second.file);
+        assert_eq!(fs::read_to_string(&first.file).unwrap(), "a.txt\nb.txt\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&first.file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        first.discard();
+        second.discard();
+    }
+
+    #[test]
+    fn test_rename_pattern() {
+        let pattern = RenamePattern::parse(r"IMG_(\d+)/photo_$1").unwrap();
+        assert_eq!(pattern.apply("IMG_0042.JPG", 1), "photo_0042.JPG");
+        assert!(!pattern.matches("other.jpg"));
+
+        let pattern = RenamePattern::parse(r"^.*\./trip_{n:03}./ext=jpg").unwrap();
+        assert_eq!(pattern.apply("a.jpeg", 1), "trip_001.jpg");
+        assert_eq!(pattern.apply("b.jpeg", 12), "trip_012.jpg");
+        assert_eq!(pattern.apply("README", 3), "README");
+
+        let pattern = RenamePattern::parse(r"(?<num>\d+)/${num}-{n}").unwrap();
+        assert_eq!(pattern.apply("take 7", 2), "take 7-2");
+        assert!(RenamePattern::parse("x/{n:ab}").is_err());
+
+        let pattern = RenamePattern::parse("_/ /title").unwrap();
+        assert_eq!(pattern.apply("my_HOLIDAY_notes.TXT", 1), "My Holiday Notes.TXT");
+
+        let pattern = RenamePattern::parse("//lower,ext=").unwrap();
+        assert_eq!(pattern.apply("README.md", 1), "readme");
+        assert_eq!(pattern.apply(".Config", 2), ".config");
+
+        assert!(RenamePattern::parse("(/x").is_err());
+        assert!(RenamePattern::parse("a/b/shout").is_err());
+    }
+
+    #[test]
+    fn test_rename_batch_swaps_and_cycles() {
+        let dir = temp_dir("rename-cycle");
+        for name in ["a", "b", "c"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+
+        let plan = RenamePlan::new(
+            &dir,
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("b".to_string(), "c".to_string()),
+                ("c".to_string(), "a".to_string()),
+            ],
+        )
+        .unwrap();
+        plan.execute().unwrap();
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "c");
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.join("c")).unwrap(), "b");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rename_batch_rolls_back() {
+        let dir = temp_dir("rename-rollback");
+        fs::write(dir.join("a"), b"a").unwrap();
+        fs::write(dir.join("b"), b"b").unwrap();
+
+        let pairs = vec![
+            (dir.join("a"), dir.join("b")),
+            (dir.join("b"), dir.join("missing").join("b")),
+        ];
+        assert!(rename_batch(&pairs).is_err());
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "b");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        // A target taken by the time it is placed is not replaced
+        let pairs = vec![(dir.join("a"), dir.join("c")), (dir.join("b"), dir.join("c"))];
+        let err = rename_batch(&pairs).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "b");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use std::cmp::Ordering;
+
+use crate::app::{git_status_priority, Entry};
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// What the file list is ordered by.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SortKey {
+    /// Case-insensitive name.
+    #[default]
+    Name,
+    /// Name with runs of digits compared by value, so `file2` < `file10`.
+    Natural,
+    /// Largest first.
+    Size,
+    /// Most recently modified first.
+    Modified,
+    /// Extension, then name.
+    Extension,
+    /// Most significant git status (conflicts, changes) first.
+    Git,
+}
+
+/// Order of the file list. `..` always stays on top.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub key: SortKey,
+    pub reverse: bool,
+    /// Group directories before files regardless of the key
+    pub dirs_first: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort {
+            key: SortKey::Name,
+            reverse: false,
+            dirs_first: true,
+        }
+    }
+}
+
+// =============================================================================
+// SortKey Implementation
+// =============================================================================
+
+impl SortKey {
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Natural => "natural",
+            SortKey::Size => "size",
+            SortKey::Modified => "modified",
+            SortKey::Extension => "extension",
+            SortKey::Git => "git",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<SortKey> {
+        [
+            SortKey::Name,
+            SortKey::Natural,
+            SortKey::Size,
+            SortKey::Modified,
+            SortKey::Extension,
+            SortKey::Git,
+        ]
+        .into_iter()
+        .find(|key| key.label() == value)
+    }
+}
+
+// =============================================================================
+// Sort Implementation
+// =============================================================================
+
+impl Sort {
+    /// Short description for the list title, e.g. "size, reversed".
+    pub fn label(&self) -> String {
+        let mut label = self.key.label().to_string();
+        if self.reverse {
+            label.push_str(", reversed");
+        }
+        if !self.dirs_first {
+            label.push_str(", dirs mixed");
+        }
+        label
+    }
+
+    pub fn compare(&self, a: &Entry, b: &Entry) -> Ordering {
+        if self.dirs_first && a.is_dir != b.is_dir {
+            return b.is_dir.cmp(&a.is_dir);
+        }
+        let ordering = match self.key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Natural => natural_cmp(&a.name_lower, &b.name_lower),
+            SortKey::Size => b.size.cmp(&a.size),
+            SortKey::Modified => b.modified.cmp(&a.modified),
+            SortKey::Extension => extension(&a.name_lower).cmp(extension(&b.name_lower)),
+            SortKey::Git => {
+                let priority = |e: &Entry| e.git_status.map_or(0, git_status_priority);
+                priority(b).cmp(&priority(a))
+            }
+        }
+        // Ties fall back to the name so the order is stable across refreshes
+        .then_with(|| a.name_lower.cmp(&b.name_lower));
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Compares names chunk by chunk, runs of ASCII digits by numeric value.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x_digits, x_rest) = split_digits(a);
+                let (y_digits, y_rest) = split_digits(b);
+                let (x_value, y_value) = (
+                    x_digits.trim_start_matches('0'),
+                    y_digits.trim_start_matches('0'),
+                );
+                // Without leading zeros the longer number is larger
+                let ordering = x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x_digits.len().cmp(&y_digits.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = x_rest;
+                b = y_rest;
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a = &a[x.len_utf8()..];
+                b = &b[y.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn split_digits(text: &str) -> (&str, &str) {
+    let end = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    text.split_at(end)
+}
+
+/// Extension of a file name; empty for none and for dotfiles like `.bashrc`.
+fn extension(name: &str) -> &str {
+    match name.rfind('.') {
+        Some(0) | None => "",
+        Some(i) => &name[i + 1..],
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, is_dir: bool, size: u64) -> Entry {
+        Entry {
+            name: name.into(),
+            name_lower: name.to_lowercase(),
+            is_dir,
+            size,
+            modified: None,
+            is_hidden: false,
+            readonly: false,
+            git_status: None,
+        }
+    }
+
+    fn sorted(sort: Sort, entries: &mut [Entry]) -> Vec<&str> {
+        entries.sort_by(|a, b| sort.compare(a, b));
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("file2", "file10"), Ordering::Less);
+        assert_eq!(natural_cmp("file10", "file9.txt"), Ordering::Greater);
+        assert_eq!(natural_cmp("a01", "a1"), Ordering::Greater);
+        assert_eq!(natural_cmp("v1.2", "v1.10"), Ordering::Less);
+        assert_eq!(natural_cmp("abc", "abc"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let mut entries = [
+            entry("file10.txt", false, 5),
+            entry("docs", true, 0),
+            entry("file2.rs", false, 50),
+            entry("Makefile", false, 5),
+        ];
+        let natural = Sort {
+            key: SortKey::Natural,
+            ..Sort::default()
+        };
+        assert_eq!(
+            sorted(natural, &mut entries),
+            vec!["docs", "file2.rs", "file10.txt", "Makefile"]
+        );
+
+        let size = Sort {
+            key: SortKey::Size,
+            dirs_first: false,
+            ..Sort::default()
+        };
+        assert_eq!(
+            sorted(size, &mut entries),
+            vec!["file2.rs", "file10.txt", "Makefile", "docs"]
+        );
+
+        // Reversing keeps directories on top
+        let extension = Sort {
+            key: SortKey::Extension,
+            reverse: true,
+            dirs_first: true,
+        };
+        assert_eq!(
+            sorted(extension, &mut entries),
+            vec!["docs", "file10.txt", "file2.rs", "Makefile"]
+        );
+    }
+}
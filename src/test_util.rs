@@ -0,0 +1,10 @@
+use std::{fs, path::PathBuf};
+
+/// An empty directory for the test called `name`, unique to this process.
+/// Whatever an earlier run left there is removed first.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fylins-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,949 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::events::Message;
+use crate::trash::{self, TrashItem};
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Size of the buffer used when copying file contents
+const COPY_CHUNK_SIZE: usize = 256 * 1024;
+
+/// How often a paused job checks whether it was resumed or cancelled
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Kind of a transfer job.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferKind {
+    Copy,
+    Move,
+}
+
+/// A single source/destination pair of a job.
+#[derive(Debug, Clone)]
+pub struct TransferItem {
+    pub src: PathBuf,
+    pub dest: PathBuf,
+    /// Move an existing destination to the trash before transferring.
+    pub overwrite: bool,
+}
+
+/// How symbolic links inside a copied tree are handled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymlinkPolicy {
+    /// Recreate the link, pointing to the same target.
+    Link,
+    /// Copy whatever the link points to.
+    Follow,
+}
+
+/// Controls how faithfully a copy replicates its source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CopyPolicy {
+    pub preserve_timestamps: bool,
+    pub preserve_permissions: bool,
+    pub symlinks: SymlinkPolicy,
+}
+
+struct Job {
+    id: u64,
+    kind: TransferKind,
+    items: Vec<TransferItem>,
+    policy: CopyPolicy,
+}
+
+/// Progress of the running job, shared with the UI thread.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub kind: TransferKind,
+    pub files_done: u64,
+    pub files_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// Time spent transferring, excluding pauses.
+    pub elapsed: Duration,
+    pub paused: bool,
+}
+
+impl Progress {
+    /// Average throughput in bytes per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_done as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated time remaining, if there is enough data to guess.
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.throughput();
+        if rate <= 0.0 {
+            return None;
+        }
+        let remaining = self.bytes_total.saturating_sub(self.bytes_done) as f64;
+        Some(Duration::from_secs_f64(remaining / rate))
+    }
+}
+
+/// Result of a finished (or cancelled) job.
+#[derive(Debug)]
+pub struct JobOutcome {
+    /// Id `TransferEngine::submit` returned for the job.
+    pub id: u64,
+    pub kind: TransferKind,
+    pub total: usize,
+    /// Items that were transferred, as (source, destination) pairs.
+    pub completed: Vec<(PathBuf, PathBuf)>,
+    /// Items that failed, with the error message.
+    pub failed: Vec<(PathBuf, String)>,
+    /// Items that were transferred but left something behind, e.g. a
+    /// source that was only partly removed.
+    pub warnings: Vec<(PathBuf, String)>,
+    /// Existing destinations that were overwritten (moved to the trash).
+    pub replaced: Vec<TrashItem>,
+    pub cancelled: bool,
+}
+
+impl JobOutcome {
+    /// Ok if every item was transferred in full, otherwise the first
+    /// problem.
+    pub fn check(&self) -> io::Result<()> {
+        if let Some((path, e)) = self.failed.first().or(self.warnings.first()) {
+            return Err(io::Error::other(format!("{}: {}", path.display(), e)));
+        }
+        if self.cancelled {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        Ok(())
+    }
+}
+
+/// State shared between the UI thread and the worker.
+#[derive(Default)]
+struct Shared {
+    progress: Mutex<Option<Progress>>,
+    cancel: AtomicBool,
+    paused: AtomicBool,
+    queued: AtomicUsize,
+}
+
+/// Runs copy and move jobs on a background worker, one job at a time.
+pub struct TransferEngine {
+    jobs: Sender<Job>,
+    shared: Arc<Shared>,
+    /// Id of the last job submitted
+    last_id: AtomicU64,
+}
+
+impl Default for CopyPolicy {
+    /// A faithful replica: metadata is kept and links stay links.
+    fn default() -> Self {
+        CopyPolicy {
+            preserve_timestamps: true,
+            preserve_permissions: true,
+            symlinks: SymlinkPolicy::Link,
+        }
+    }
+}
+
+impl SymlinkPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SymlinkPolicy::Link => "copy as links",
+            SymlinkPolicy::Follow => "follow links",
+        }
+    }
+}
+
+/// Notifications sent while copying.
+enum CopyEvent {
+    Bytes(u64),
+    FileDone,
+}
+
+// =============================================================================
+// TransferEngine Implementation
+// =============================================================================
+
+impl TransferEngine {
+    /// Starts the worker. The outcome of every job is posted to `outcomes`
+    /// as `Message::TransferFinished`.
+    pub fn new(outcomes: Sender<Message>) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let shared = Arc::new(Shared::default());
+
+        let worker_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            for job in job_rx {
+                worker_shared.queued.fetch_sub(1, Ordering::SeqCst);
+                let outcome = run_job(job, &worker_shared);
+                if outcomes.send(Message::TransferFinished(outcome)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        TransferEngine {
+            jobs: job_tx,
+            shared,
+            last_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Queues a job. Copies follow `policy`; moves always keep metadata and
+    /// links. Returns the id its outcome will carry, or None if the worker
+    /// is gone.
+    pub fn submit(
+        &self,
+        kind: TransferKind,
+        items: Vec<TransferItem>,
+        policy: CopyPolicy,
+    ) -> Option<u64> {
+        let policy = match kind {
+            TransferKind::Copy => policy,
+            TransferKind::Move => CopyPolicy::default(),
+        };
+        let id = self.last_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.shared.queued.fetch_add(1, Ordering::SeqCst);
+        let job = Job {
+            id,
+            kind,
+            items,
+            policy,
+        };
+        self.jobs.send(job).is_ok().then_some(id)
+    }
+
+    /// Snapshot of the running job's progress.
+    pub fn progress(&self) -> Option<Progress> {
+        self.shared
+            .progress
+            .lock()
+            .ok()
+            .and_then(|p| p.clone())
+    }
+
+    /// Number of jobs waiting behind the running one.
+    pub fn queued(&self) -> usize {
+        self.shared.queued.load(Ordering::SeqCst)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.progress().is_some() || self.queued() > 0
+    }
+
+    /// Cancels the running job. Queued jobs are not affected.
+    pub fn cancel(&self) {
+        self.shared.cancel.store(true, Ordering::SeqCst);
+    }
+
+    /// Pauses or resumes the worker. Returns true if it is now paused.
+    pub fn toggle_pause(&self) -> bool {
+        !self.shared.paused.fetch_xor(true, Ordering::SeqCst)
+    }
+}
+
+// =============================================================================
+// Worker
+// =============================================================================
+
+fn run_job(job: Job, shared: &Shared) -> JobOutcome {
+    shared.cancel.store(false, Ordering::SeqCst);
+
+    let policy = job.policy;
+    let follow = policy.symlinks == SymlinkPolicy::Follow;
+    let sizes: Vec<(u64, u64)> = job.items.iter().map(|i| measure(&i.src, follow)).collect();
+    let mut progress = Progress {
+        kind: job.kind,
+        files_done: 0,
+        files_total: sizes.iter().map(|s| s.0).sum(),
+        bytes_done: 0,
+        bytes_total: sizes.iter().map(|s| s.1).sum(),
+        elapsed: Duration::ZERO,
+        paused: false,
+    };
+    publish(shared, Some(progress.clone()));
+
+    let started = Instant::now();
+    let mut paused_for = Duration::ZERO;
+    let mut outcome = JobOutcome {
+        id: job.id,
+        kind: job.kind,
+        total: job.items.len(),
+        completed: Vec::new(),
+        failed: Vec::new(),
+        warnings: Vec::new(),
+        replaced: Vec::new(),
+        cancelled: false,
+    };
+
+    for (item, (files, bytes)) in job.items.iter().zip(sizes) {
+        if shared.cancel.load(Ordering::SeqCst) {
+            outcome.cancelled = true;
+            break;
+        }
+
+        let mut on_event = |event: CopyEvent| -> io::Result<()> {
+            match event {
+                CopyEvent::Bytes(n) => progress.bytes_done += n,
+                CopyEvent::FileDone => progress.files_done += 1,
+            }
+            wait_while_paused(shared, &mut progress, &mut paused_for)?;
+            progress.elapsed = started.elapsed().saturating_sub(paused_for);
+            publish(shared, Some(progress.clone()));
+            Ok(())
+        };
+
+        let replaced = if item.overwrite && exists(&item.dest) {
+            match trash::move_to_trash(&item.dest) {
+                Ok(trashed) => Some(trashed),
+                Err(e) => {
+                    outcome.failed.push((
+                        item.src.clone(),
+                        format!("Cannot replace existing item: {}", e),
+                    ));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+
+        let result = match job.kind {
+            TransferKind::Move => match rename_no_replace(&item.src, &item.dest) {
+                Ok(_) => {
+                    let _ = on_event(CopyEvent::Bytes(bytes));
+                    for _ in 0..files {
+                        let _ = on_event(CopyEvent::FileDone);
+                    }
+                    Ok(None)
+                }
+                Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                    move_across_devices(&item.src, &item.dest, &policy, &mut on_event)
+                }
+                Err(e) => Err(e),
+            },
+            TransferKind::Copy => {
+                copy_fresh(&item.src, &item.dest, &policy, &mut on_event).map(|_| None)
+            }
+        };
+
+        match result {
+            Ok(warning) => {
+                outcome
+                    .completed
+                    .push((item.src.clone(), item.dest.clone()));
+                outcome.replaced.extend(replaced);
+                outcome
+                    .warnings
+                    .extend(warning.map(|w| (item.src.clone(), w)));
+            }
+            Err(e) => {
+                // Put the overwritten item back where it was
+                if let Some(trashed) = replaced {
+                    let _ = trash::restore(&trashed);
+                }
+                if shared.cancel.load(Ordering::SeqCst) {
+                    outcome.cancelled = true;
+                    break;
+                }
+                outcome.failed.push((item.src.clone(), e.to_string()));
+            }
+        }
+    }
+
+    publish(shared, None);
+    outcome
+}
+
+fn publish(shared: &Shared, progress: Option<Progress>) {
+    if let Ok(mut slot) = shared.progress.lock() {
+        *slot = progress;
+    }
+}
+
+/// Blocks while the engine is paused. Fails with `Interrupted` on cancel.
+fn wait_while_paused(
+    shared: &Shared,
+    progress: &mut Progress,
+    paused_for: &mut Duration,
+) -> io::Result<()> {
+    if shared.paused.load(Ordering::SeqCst) {
+        progress.paused = true;
+        publish(shared, Some(progress.clone()));
+        while shared.paused.load(Ordering::SeqCst) && !shared.cancel.load(Ordering::SeqCst) {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+            *paused_for += PAUSE_POLL_INTERVAL;
+        }
+        progress.paused = false;
+    }
+    if shared.cancel.load(Ordering::SeqCst) {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+    }
+    Ok(())
+}
+
+/// Counts the files and bytes below `path`. Symlinks count as empty files
+/// unless `follow` is set.
+fn measure(path: &Path, follow: bool) -> (u64, u64) {
+    measure_inner(path, follow, &mut HashSet::new())
+}
+
+fn measure_inner(path: &Path, follow: bool, visited: &mut HashSet<PathBuf>) -> (u64, u64) {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return (0, 0);
+    };
+    let meta = match fs::metadata(path) {
+        Ok(target) if follow && meta.file_type().is_symlink() => target,
+        _ if meta.file_type().is_symlink() => return (1, 0),
+        _ => meta,
+    };
+    if meta.is_dir() {
+        // Guard against following a link back into one of its ancestors
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !visited.insert(canonical.clone()) {
+            return (0, 0);
+        }
+        let total = fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| measure_inner(&e.path(), follow, visited))
+                    .fold((0, 0), |acc, m| (acc.0 + m.0, acc.1 + m.1))
+            })
+            .unwrap_or((0, 0));
+        visited.remove(&canonical);
+        total
+    } else {
+        (1, meta.len())
+    }
+}
+
+// =============================================================================
+// Copying
+// =============================================================================
+
+/// Copies `src` to `dst`. If the copy fails, a partially written
+/// destination is removed, unless it existed beforehand.
+fn copy_fresh(
+    src: &Path,
+    dst: &Path,
+    policy: &CopyPolicy,
+    on_event: &mut dyn FnMut(CopyEvent) -> io::Result<()>,
+) -> io::Result<()> {
+    let dest_existed = exists(dst);
+    let result = copy_path_with(src, dst, policy, on_event);
+    if result.is_err() && !dest_existed {
+        let _ = remove_path(dst);
+    }
+    result
+}
+
+/// Moves `src` to another filesystem by copying, verifying the copy and only
+/// then deleting the source. The source stays untouched if copying or
+/// verification fails. Once the copy is verified the item has moved; a
+/// source that could not be fully removed is returned as a warning.
+fn move_across_devices(
+    src: &Path,
+    dst: &Path,
+    policy: &CopyPolicy,
+    on_event: &mut dyn FnMut(CopyEvent) -> io::Result<()>,
+) -> io::Result<Option<String>> {
+    copy_fresh(src, dst, policy, on_event)?;
+    if let Err(e) = verify_copy(src, dst) {
+        let _ = remove_path(dst);
+        return Err(e);
+    }
+
+    Ok(remove_path(src)
+        .err()
+        .map(|e| format!("Copied, but could not fully remove the source: {}", e)))
+}
+
+/// Checks that every file below `src` exists in `dst` with the same size,
+/// and that copied links point to the same targets.
+fn verify_copy(src: &Path, dst: &Path) -> io::Result<()> {
+    let mismatch = |what: &str| {
+        io::Error::other(format!(
+            "Verification failed: {} mismatch for {}",
+            what,
+            dst.display()
+        ))
+    };
+
+    let src_meta = fs::symlink_metadata(src)?;
+    let dst_meta = fs::symlink_metadata(dst)?;
+    if src_meta.file_type().is_symlink() && dst_meta.file_type().is_symlink() {
+        if fs::read_link(src)? != fs::read_link(dst)? {
+            return Err(mismatch("link target"));
+        }
+        return Ok(());
+    }
+
+    // A followed link is compared with what it points to
+    let src_meta = fs::metadata(src)?;
+    let dst_meta = fs::metadata(dst)?;
+    if src_meta.is_dir() != dst_meta.is_dir() {
+        return Err(mismatch("type"));
+    }
+
+    if src_meta.is_dir() {
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            verify_copy(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else if src_meta.len() != dst_meta.len() {
+        return Err(mismatch("size"));
+    }
+    Ok(())
+}
+
+/// Recreates the symlink `src` at `dst`, pointing to the same target.
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    let target = fs::read_link(src)?;
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, dst)
+    }
+
+    #[cfg(windows)]
+    {
+        if fs::metadata(src).map(|m| m.is_dir()).unwrap_or(false) {
+            std::os::windows::fs::symlink_dir(target, dst)
+        } else {
+            std::os::windows::fs::symlink_file(target, dst)
+        }
+    }
+}
+
+fn copy_path_with(
+    src: &Path,
+    dst: &Path,
+    policy: &CopyPolicy,
+    on_event: &mut dyn FnMut(CopyEvent) -> io::Result<()>,
+) -> io::Result<()> {
+    let meta = fs::symlink_metadata(src)?;
+    // Dangling links have nothing to follow and are always kept as links
+    if meta.file_type().is_symlink()
+        && (policy.symlinks == SymlinkPolicy::Link || fs::metadata(src).is_err())
+    {
+        copy_symlink(src, dst)?;
+        return on_event(CopyEvent::FileDone);
+    }
+
+    if src.is_dir() {
+        let mut visited = HashSet::new();
+        copy_dir_inner(src, dst, policy, &mut visited, on_event)
+    } else {
+        copy_file_chunked(src, dst, policy, on_event)
+    }
+}
+
+fn copy_dir_inner(
+    src: &Path,
+    dst: &Path,
+    policy: &CopyPolicy,
+    visited: &mut HashSet<PathBuf>,
+    on_event: &mut dyn FnMut(CopyEvent) -> io::Result<()>,
+) -> io::Result<()> {
+    // Canonicalize to detect cycles (resolves symlinks)
+    let canonical_src = src.canonicalize().unwrap_or_else(|_| src.to_path_buf());
+
+    if !visited.insert(canonical_src.clone()) {
+        return Err(io::Error::other(format!(
+            "Symlink cycle detected: {}",
+            src.display()
+        )));
+    }
+
+    fs::create_dir(dst)?;
+
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        let metadata = fs::symlink_metadata(&src_path)?;
+
+        if metadata.file_type().is_symlink()
+            && (policy.symlinks == SymlinkPolicy::Link || fs::metadata(&src_path).is_err())
+        {
+            copy_symlink(&src_path, &dst_path)?;
+            on_event(CopyEvent::FileDone)?;
+        } else if src_path.is_dir() {
+            copy_dir_inner(&src_path, &dst_path, policy, visited, on_event)?;
+        } else {
+            copy_file_chunked(&src_path, &dst_path, policy, on_event)?;
+        }
+    }
+
+    // Siblings may reach the same directory through different links
+    visited.remove(&canonical_src);
+
+    // Applied last, so copying the children cannot bump the mtime or
+    // trip over a read-only directory
+    apply_metadata(&fs::metadata(src)?, dst, policy)
+}
+
+/// Copies file contents in chunks, reporting progress after each chunk.
+/// Refuses to overwrite an existing destination.
+fn copy_file_chunked(
+    src: &Path,
+    dst: &Path,
+    policy: &CopyPolicy,
+    on_event: &mut dyn FnMut(CopyEvent) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut reader = fs::File::open(src)?;
+    let metadata = reader.metadata()?;
+    let mut writer = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dst)?;
+
+    let result = (|| {
+        let mut buffer = vec![0u8; COPY_CHUNK_SIZE];
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buffer[..n])?;
+            on_event(CopyEvent::Bytes(n as u64))?;
+        }
+        writer.flush()?;
+        if policy.preserve_timestamps {
+            writer.set_times(file_times(&metadata))?;
+        }
+        if policy.preserve_permissions {
+            fs::set_permissions(dst, metadata.permissions())?;
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        drop(writer);
+        let _ = fs::remove_file(dst);
+        return Err(e);
+    }
+    on_event(CopyEvent::FileDone)
+}
+
+/// Copies timestamps and permissions of a directory as the policy asks.
+fn apply_metadata(metadata: &fs::Metadata, dst: &Path, policy: &CopyPolicy) -> io::Result<()> {
+    if policy.preserve_timestamps {
+        open_for_times(dst)?.set_times(file_times(metadata))?;
+    }
+    if policy.preserve_permissions {
+        fs::set_permissions(dst, metadata.permissions())?;
+    }
+    Ok(())
+}
+
+fn file_times(metadata: &fs::Metadata) -> fs::FileTimes {
+    let mut times = fs::FileTimes::new();
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    times
+}
+
+/// Opens a directory so its timestamps can be changed.
+#[cfg(unix)]
+fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    fs::File::open(path)
+}
+
+#[cfg(windows)]
+fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    // Directories can only be opened with backup semantics
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// Renames `src` to `dest` like `fs::rename`, but fails with
+/// `AlreadyExists` instead of replacing an existing `dest`.
+pub fn rename_no_replace(src: &Path, dest: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let from = CString::new(src.as_os_str().as_bytes())?;
+        let to = CString::new(dest.as_os_str().as_bytes())?;
+        // SAFETY: both paths are valid NUL-terminated strings
+        let result = unsafe {
+            libc::renameat2(
+                libc::AT_FDCWD,
+                from.as_ptr(),
+                libc::AT_FDCWD,
+                to.as_ptr(),
+                libc::RENAME_NOREPLACE,
+            )
+        };
+        if result == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        // Filesystems without RENAME_NOREPLACE fall back to checking first
+        if !matches!(err.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS)) {
+            return Err(err);
+        }
+    }
+
+    if exists(dest) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+    fs::rename(src, dest)
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) => Err(e),
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::sync::mpsc::Receiver;
+
+    fn wait_for_outcome(outcomes: &Receiver<Message>) -> JobOutcome {
+        match outcomes.recv_timeout(Duration::from_secs(5)) {
+            Ok(Message::TransferFinished(outcome)) => outcome,
+            Ok(_) => unreachable!(),
+            Err(_) => panic!("transfer did not finish"),
+        }
+    }
+
+    #[test]
+    fn test_progress_eta() {
+        let progress = Progress {
+            kind: TransferKind::Copy,
+            files_done: 1,
+            files_total: 2,
+            bytes_done: 100,
+            bytes_total: 300,
+            elapsed: Duration::from_secs(2),
+            paused: false,
+        };
+        assert_eq!(progress.throughput(), 50.0);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn test_measure_tree() {
+        let dir = temp_dir("transfer-measure");
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("a"), b"12345").unwrap();
+        fs::write(dir.join("sub").join("b"), b"123").unwrap();
+        assert_eq!(measure(&dir, false), (2, 8));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_job_in_background() {
+        let dir = temp_dir("transfer-copy");
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested").join("file.txt"), b"hello").unwrap();
+
+        let (sender, outcomes) = mpsc::channel();
+        let engine = TransferEngine::new(sender);
+        let dest = dir.join("dest");
+        let id = engine.submit(
+            TransferKind::Copy,
+            vec![TransferItem {
+                src: src.clone(),
+                dest: dest.clone(),
+                overwrite: false,
+            }],
+            CopyPolicy::default(),
+        );
+        assert!(id.is_some());
+
+        let outcome = wait_for_outcome(&outcomes);
+        assert_eq!(Some(outcome.id), id);
+        assert_eq!(outcome.completed.len(), 1);
+        assert!(outcome.failed.is_empty());
+        assert_eq!(
+            fs::read(dest.join("nested").join("file.txt")).unwrap(),
+            b"hello"
+        );
+        assert!(!engine.is_busy());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_move_across_devices_removes_source() {
+        let dir = temp_dir("transfer-xdev");
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested").join("file.txt"), b"hello").unwrap();
+
+        let dest = dir.join("dest");
+        let warning =
+            move_across_devices(&src, &dest, &CopyPolicy::default(), &mut |_| Ok(())).unwrap();
+        assert_eq!(warning, None);
+        assert!(!src.exists());
+        assert_eq!(
+            fs::read(dest.join("nested").join("file.txt")).unwrap(),
+            b"hello"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_move_across_devices_keeps_source_on_failure() {
+        let dir = temp_dir("transfer-xdev-fail");
+        let src = dir.join("file.txt");
+        fs::write(&src, b"hello").unwrap();
+
+        let dest = dir.join("missing").join("file.txt");
+        assert!(move_across_devices(&src, &dest, &CopyPolicy::default(), &mut |_| Ok(())).is_err());
+        assert_eq!(fs::read(&src).unwrap(), b"hello");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_move_job_refuses_existing_destination() {
+        let dir = temp_dir("transfer-move-existing");
+        fs::write(dir.join("a"), b"new").unwrap();
+        fs::write(dir.join("b"), b"old").unwrap();
+
+        let (sender, outcomes) = mpsc::channel();
+        let engine = TransferEngine::new(sender);
+        engine.submit(
+            TransferKind::Move,
+            vec![TransferItem {
+                src: dir.join("a"),
+                dest: dir.join("b"),
+                overwrite: false,
+            }],
+            CopyPolicy::default(),
+        );
+
+        let outcome = wait_for_outcome(&outcomes);
+        assert!(outcome.completed.is_empty());
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(fs::read(dir.join("a")).unwrap(), b"new");
+        assert_eq!(fs::read(dir.join("b")).unwrap(), b"old");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_copy_detects_size_mismatch() {
+        let dir = temp_dir("transfer-verify");
+        fs::write(dir.join("a"), b"12345").unwrap();
+        fs::write(dir.join("b"), b"123").unwrap();
+        assert!(verify_copy(&dir.join("a"), &dir.join("b")).is_err());
+        fs::write(dir.join("b"), b"54321").unwrap();
+        assert!(verify_copy(&dir.join("a"), &dir.join("b")).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_refuses_existing_destination() {
+        let dir = temp_dir("transfer-existing");
+        fs::write(dir.join("a"), b"new").unwrap();
+        fs::write(dir.join("b"), b"old").unwrap();
+        let policy = CopyPolicy::default();
+        assert!(copy_path_with(&dir.join("a"), &dir.join("b"), &policy, &mut |_| Ok(())).is_err());
+        assert_eq!(fs::read(dir.join("b")).unwrap(), b"old");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_policies() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = temp_dir("transfer-policy");
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("real")).unwrap();
+        fs::write(src.join("real").join("file.txt"), b"hello").unwrap();
+        symlink("real", src.join("link")).unwrap();
+        fs::set_permissions(src.join("real").join("file.txt"), fs::Permissions::from_mode(0o640))
+            .unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        fs::File::options()
+            .write(true)
+            .open(src.join("real").join("file.txt"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let linked = dir.join("linked");
+        copy_path_with(&src, &linked, &CopyPolicy::default(), &mut |_| Ok(())).unwrap();
+        assert_eq!(fs::read_link(linked.join("link")).unwrap(), PathBuf::from("real"));
+        let meta = fs::metadata(linked.join("real").join("file.txt")).unwrap();
+        assert_eq!(meta.modified().unwrap(), old);
+        assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+
+        let followed = dir.join("followed");
+        let policy = CopyPolicy {
+            preserve_timestamps: false,
+            preserve_permissions: true,
+            symlinks: SymlinkPolicy::Follow,
+        };
+        copy_path_with(&src, &followed, &policy, &mut |_| Ok(())).unwrap();
+        let link_meta = fs::symlink_metadata(followed.join("link")).unwrap();
+        assert!(link_meta.is_dir());
+        assert_eq!(fs::read(followed.join("link").join("file.txt")).unwrap(), b"hello");
+        assert_ne!(
+            fs::metadata(followed.join("real").join("file.txt")).unwrap().modified().unwrap(),
+            old
+        );
+        assert_eq!(measure(&src, true), (2, 10));
+        assert_eq!(measure(&src, false), (2, 5));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,640 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::transfer::rename_no_replace;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Extension of the metadata files stored in a trash `info` directory
+const TRASH_INFO_EXTENSION: &str = "trashinfo";
+
+/// Header line required at the top of every `.trashinfo` file
+const TRASH_INFO_HEADER: &str = "[Trash Info]";
+
+/// Sticky bit, required on a shared `$topdir/.Trash` directory
+#[cfg(unix)]
+const STICKY_BIT: u32 = 0o1000;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// A file or directory that currently lives in a trash directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashItem {
+    /// Location of the item inside the trash `files` directory.
+    pub trashed_path: PathBuf,
+    /// Location of the matching `.trashinfo` file.
+    pub info_path: PathBuf,
+    /// Absolute path the item was deleted from.
+    pub original_path: PathBuf,
+    /// Deletion date as stored in the info file (YYYY-MM-DDThh:mm:ss).
+    pub deletion_date: String,
+    pub is_dir: bool,
+}
+
+impl TrashItem {
+    /// Name of the item as it was before deletion.
+    pub fn name(&self) -> String {
+        self.original_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.original_path.to_string_lossy().to_string())
+    }
+}
+
+/// A trash directory with its `files`/`info` subdirectories.
+struct TrashDir {
+    root: PathBuf,
+    /// Mount point the trash belongs to. `None` for the home trash, whose
+    /// info files store absolute paths.
+    topdir: Option<PathBuf>,
+}
+
+impl TrashDir {
+    fn files_dir(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn info_dir(&self) -> PathBuf {
+        self.root.join("info")
+    }
+}
+
+// =============================================================================
+// Public API
+// =============================================================================
+
+/// Moves a file or directory to the trash following the freedesktop.org
+/// Trash specification. Files on the home partition go to the home trash,
+/// files on other mounts go to `$topdir/.Trash/$uid` or `$topdir/.Trash-$uid`.
+pub fn move_to_trash(path: &Path) -> io::Result<TrashItem> {
+    let path = absolute(path)?;
+    let metadata = fs::symlink_metadata(&path)?;
+    let trash = trash_dir_for(&path)?;
+
+    let files_dir = trash.files_dir();
+    let info_dir = trash.info_dir();
+    create_private_dir_all(&files_dir)?;
+    create_private_dir_all(&info_dir)?;
+
+    let stored_path = match &trash.topdir {
+        Some(topdir) => path.strip_prefix(topdir).unwrap_or(&path).to_path_buf(),
+        None => path.clone(),
+    };
+    let deletion_date = format_deletion_date(SystemTime::now());
+    let contents = format!(
+        "{}\nPath={}\nDeletionDate={}\n",
+        TRASH_INFO_HEADER,
+        percent_encode(&path_bytes(&stored_path)),
+        deletion_date
+    );
+
+    // Reserve a unique name by creating the info file exclusively first
+    let base_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid path"))?;
+    let mut counter = 1;
+    let (trash_name, info_path) = loop {
+        let candidate = if counter == 1 {
+            base_name.clone()
+        } else {
+            format!("{}.{}", base_name, counter)
+        };
+        let info_path = info_dir.join(format!("{}.{}", candidate, TRASH_INFO_EXTENSION));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(mut file) if !files_dir.join(&candidate).exists() => {
+                file.write_all(contents.as_bytes())?;
+                break (candidate, info_path);
+            }
+            Ok(_) => {
+                let _ = fs::remove_file(&info_path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+        counter += 1;
+    };
+
+    let trashed_path = files_dir.join(&trash_name);
+    if let Err(e) = fs::rename(&path, &trashed_path) {
+        let _ = fs::remove_file(&info_path);
+        return Err(e);
+    }
+
+    Ok(TrashItem {
+        trashed_path,
+        info_path,
+        original_path: path,
+        deletion_date,
+        is_dir: metadata.is_dir(),
+    })
+}
+
+/// Lists the contents of the home trash and of every per-mount trash
+/// directory that belongs to the current user, newest first.
+pub fn list_items() -> Vec<TrashItem> {
+    let mut items: Vec<TrashItem> = known_trash_dirs()
+        .iter()
+        .flat_map(read_trash_dir)
+        .collect();
+    items.sort_by(|a, b| {
+        b.deletion_date
+            .cmp(&a.deletion_date)
+            .then_with(|| a.original_path.cmp(&b.original_path))
+    });
+    items
+}
+
+/// Moves a trashed item back to its original location.
+/// Fails if something already exists at the original path.
+pub fn restore(item: &TrashItem) -> io::Result<()> {
+    if let Some(parent) = item.original_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    rename_no_replace(&item.trashed_path, &item.original_path).map_err(|e| {
+        if e.kind() == io::ErrorKind::AlreadyExists {
+            io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("'{}' already exists", item.original_path.display()),
+            )
+        } else {
+            e
+        }
+    })?;
+    let _ = fs::remove_file(&item.info_path);
+    Ok(())
+}
+
+/// Permanently removes a trashed item and its info file.
+pub fn purge(item: &TrashItem) -> io::Result<()> {
+    match fs::symlink_metadata(&item.trashed_path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(&item.trashed_path)?,
+        Ok(_) => fs::remove_file(&item.trashed_path)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    fs::remove_file(&item.info_path)
+}
+
+// =============================================================================
+// Trash Directory Discovery
+// =============================================================================
+
+fn home_trash() -> io::Result<TrashDir> {
+    let data_dir = dirs_next::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No data directory"))?;
+    Ok(TrashDir {
+        root: data_dir.join("Trash"),
+        topdir: None,
+    })
+}
+
+#[cfg(unix)]
+fn trash_dir_for(path: &Path) -> io::Result<TrashDir> {
+    use std::os::unix::fs::MetadataExt;
+
+    let home = home_trash()?;
+    let device = fs::symlink_metadata(path)?.dev();
+    let home_device = existing_ancestor(&home.root)
+        .and_then(|p| fs::metadata(p).ok())
+        .map(|m| m.dev());
+    if home_device == Some(device) {
+        return Ok(home);
+    }
+
+    let topdir = mount_topdir(path, device);
+    let uid = current_uid();
+
+    // Prefer an administrator-provided $topdir/.Trash if it is safe to use
+    let shared = topdir.join(".Trash");
+    if let Ok(meta) = fs::symlink_metadata(&shared) {
+        if meta.is_dir() && meta.mode() & STICKY_BIT != 0 {
+            let root = shared.join(uid.to_string());
+            if create_private_dir_all(&root).is_ok() {
+                return Ok(TrashDir {
+                    root,
+                    topdir: Some(topdir),
+                });
+            }
+        }
+    }
+
+    let root = topdir.join(format!(".Trash-{}", uid));
+    create_private_dir_all(&root)?;
+    Ok(TrashDir {
+        root,
+        topdir: Some(topdir),
+    })
+}
+
+#[cfg(not(unix))]
+fn trash_dir_for(_path: &Path) -> io::Result<TrashDir> {
+    home_trash()
+}
+
+/// Walks up from `path` to the highest ancestor that is still on `device`.
+#[cfg(unix)]
+fn mount_topdir(path: &Path, device: u64) -> PathBuf {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut topdir = path.parent().unwrap_or(path).to_path_buf();
+    while let Some(parent) = topdir.parent() {
+        match fs::metadata(parent) {
+            Ok(meta) if meta.dev() == device => topdir = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    topdir
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+/// Returns the home trash plus any per-mount trash directories that exist.
+fn known_trash_dirs() -> Vec<TrashDir> {
+    let mut dirs: Vec<TrashDir> = home_trash().into_iter().collect();
+
+    #[cfg(unix)]
+    {
+        let uid = current_uid();
+        for mount in mount_points() {
+            let candidates = [
+                mount.join(".Trash").join(uid.to_string()),
+                mount.join(format!(".Trash-{}", uid)),
+            ];
+            for root in candidates {
+                if root.join("info").is_dir() {
+                    dirs.push(TrashDir {
+                        root,
+                        topdir: Some(mount.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    dirs
+}
+
+/// Reads the mount table on Linux. Other platforms only get the home trash.
+/// Mounts that may block or cannot hold a trash are left out.
+#[cfg(unix)]
+fn mount_points() -> Vec<PathBuf> {
+    let Ok(table) = fs::read_to_string("/proc/self/mounts") else {
+        return Vec::new();
+    };
+    let mut mounts: Vec<PathBuf> = table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            let mount = fields.next()?;
+            let fs_type = fields.next()?;
+            is_probed_filesystem(fs_type).then(|| PathBuf::from(decode_mount_field(mount)))
+        })
+        .collect();
+    mounts.sort();
+    mounts.dedup();
+    mounts
+}
+
+/// Whether the trash browser looks for a trash on a mount of `fs_type`.
+/// Network and FUSE filesystems are skipped, as a stale one would hang
+/// the UI; items trashed there can still be restored through undo.
+#[cfg(unix)]
+fn is_probed_filesystem(fs_type: &str) -> bool {
+    const SKIPPED: &[&str] = &[
+        // Pseudo filesystems
+        "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs", "debugfs",
+        "tracefs", "pstore", "bpf", "mqueue", "hugetlbfs", "configfs", "fusectl", "autofs",
+        "binfmt_misc", "efivarfs", "nsfs", "rpc_pipefs",
+        // Network filesystems
+        "nfs", "nfs4", "cifs", "smb3", "smbfs", "ncpfs", "9p", "afs", "ceph", "glusterfs",
+        "lustre", "gfs2", "ocfs2", "fuse",
+    ];
+    // fuseblk is a local disk (e.g. NTFS); other FUSE mounts may be remote
+    !SKIPPED.contains(&fs_type) && !fs_type.starts_with("fuse.")
+}
+
+/// Decodes the octal escapes (`\040` for space, ...) used in /proc/mounts.
+#[cfg(unix)]
+fn decode_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if let Some(Ok(value)) = field.get(i + 1..i + 4).map(|d| u8::from_str_radix(d, 8)) {
+                out.push(value);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn read_trash_dir(trash: &TrashDir) -> Vec<TrashItem> {
+    let Ok(entries) = fs::read_dir(trash.info_dir()) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let info_path = e.path();
+            if info_path.extension().and_then(|ext| ext.to_str()) != Some(TRASH_INFO_EXTENSION) {
+                return None;
+            }
+            let trash_name = info_path.file_stem()?.to_os_string();
+            let trashed_path = trash.files_dir().join(trash_name);
+            let meta = fs::symlink_metadata(&trashed_path).ok()?;
+            let contents = fs::read_to_string(&info_path).ok()?;
+            let (stored_path, deletion_date) = parse_trash_info(&contents)?;
+            let original_path = match &trash.topdir {
+                Some(topdir) if stored_path.is_relative() => topdir.join(stored_path),
+                _ => stored_path,
+            };
+            Some(TrashItem {
+                trashed_path,
+                info_path,
+                original_path,
+                deletion_date,
+                is_dir: meta.is_dir(),
+            })
+        })
+        .collect()
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn absolute(path: &Path) -> io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+#[cfg(unix)]
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+fn create_private_dir_all(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(path)
+    }
+    #[cfg(not(unix))]
+    {
+        fs::create_dir_all(path)
+    }
+}
+
+/// Parses a `.trashinfo` file, returning the stored path and deletion date.
+fn parse_trash_info(contents: &str) -> Option<(PathBuf, String)> {
+    let mut lines = contents.lines().map(str::trim);
+    if lines.next()? != TRASH_INFO_HEADER {
+        return None;
+    }
+
+    let mut path = None;
+    let mut date = String::new();
+    for line in lines {
+        if line.starts_with('[') {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Path=") {
+            path = Some(path_from_bytes(percent_decode(value)));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            date = value.to_string();
+        }
+    }
+    path.map(|p| (p, date))
+}
+
+/// Raw bytes of a path, so names that are not valid UTF-8 survive the
+/// round trip through a `.trashinfo` file.
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Percent-encodes a path for the `Path=` key, leaving `/` and unreserved
+/// characters as they are.
+fn percent_encode(value: &[u8]) -> String {
+    let mut out = String::with_capacity(value.len());
+    for &b in value {
+        if b.is_ascii_alphanumeric() || b"/-_.~!*'()".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(Ok(b)) = value.get(i + 1..i + 3).map(|h| u8::from_str_radix(h, 16)) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Formats a deletion date as YYYY-MM-DDThh:mm:ss in local time, as the
+/// spec requires.
+fn format_deletion_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    format_timestamp(secs + utc_offset(secs))
+}
+
+/// Formats seconds since 1970-01-01T00:00:00 as YYYY-MM-DDThh:mm:ss.
+fn format_timestamp(secs: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    let time_of_day = secs.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        (time_of_day % 3600) / 60,
+        time_of_day % 60
+    )
+}
+
+/// Seconds the local timezone is ahead of UTC at `secs`.
+#[cfg(unix)]
+fn utc_offset(secs: i64) -> i64 {
+    let time = secs as libc::time_t;
+    // SAFETY: localtime_r only writes to the struct it is given
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return 0;
+    }
+    tm.tm_gmtoff as i64
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_secs: i64) -> i64 {
+    0
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_percent_encode_roundtrip() {
+        let path = "/home/user/My Files/100%_done.txt";
+        let encoded = percent_encode(path.as_bytes());
+        assert_eq!(encoded, "/home/user/My%20Files/100%25_done.txt");
+        assert_eq!(percent_decode(&encoded), path.as_bytes());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path_roundtrip() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/caf\xe9.txt"));
+        let encoded = percent_encode(&path_bytes(path));
+        assert_eq!(encoded, "/tmp/caf%E9.txt");
+        assert_eq!(path_from_bytes(percent_decode(&encoded)), path);
+    }
+
+    #[test]
+    fn test_restore_keeps_existing_file() {
+        let dir = temp_dir("trash-restore");
+        let trashed = dir.join("trashed");
+        let original = dir.join("original");
+        let info = dir.join("trashed.trashinfo");
+        fs::write(&trashed, "old").unwrap();
+        fs::write(&original, "new").unwrap();
+        fs::write(&info, "").unwrap();
+        let item = TrashItem {
+            trashed_path: trashed.clone(),
+            info_path: info.clone(),
+            original_path: original.clone(),
+            deletion_date: String::new(),
+            is_dir: false,
+        };
+
+        let err = restore(&item).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&original).unwrap(), "new");
+        assert!(trashed.exists() && info.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_trash_info() {
+        let contents = "[Trash Info]\nPath=foo/bar%20baz.txt\nDeletionDate=2004-08-31T22:32:08\n";
+        let (path, date) = parse_trash_info(contents).unwrap();
+        assert_eq!(path, PathBuf::from("foo/bar baz.txt"));
+        assert_eq!(date, "2004-08-31T22:32:08");
+    }
+
+    #[test]
+    fn test_parse_trash_info_invalid() {
+        assert!(parse_trash_info("Path=/tmp/x\n").is_none());
+        assert!(parse_trash_info("[Trash Info]\nDeletionDate=2004-08-31T22:32:08\n").is_none());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(1_093_991_528), "2004-08-31T22:32:08");
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00");
+        assert_eq!(format_timestamp(-1), "1969-12-31T23:59:59");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_probed_filesystem() {
+        assert!(is_probed_filesystem("ext4"));
+        assert!(is_probed_filesystem("fuseblk"));
+        assert!(!is_probed_filesystem("nfs4"));
+        assert!(!is_probed_filesystem("fuse.sshfs"));
+        assert!(!is_probed_filesystem("proc"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_decode_mount_field() {
+        assert_eq!(decode_mount_field("/media/usb\\040disk"), "/media/usb disk");
+        assert_eq!(decode_mount_field("/mnt/data"), "/mnt/data");
+    }
+}
@@ -2,13 +2,21 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
-use std::{path::Path, time::SystemTime};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
-use crate::app::{App, GitStatus, Mode, Preview};
-use crate::highlight::highlight_code;
+use crate::app::{App, GitStatus, Mode, ParentColumn, Preview};
+use crate::grep::{ContentSearch, Matcher};
+use crate::bookmarks::Bookmarks;
+use crate::paste::PastePlan;
+use crate::trash::TrashItem;
+use crate::transfer::{CopyPolicy, Progress, TransferKind};
 
 // =============================================================================
 // Constants
@@ -177,29 +185,59 @@ fn format_time(time: Option<SystemTime>) -> String {
     }
 }
 
+/// Formats a duration as M:SS or H:MM:SS.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
 fn is_leap_year(year: u64) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
 }
 
 // =============================================================================
 // UI Rendering
 // =============================================================================
 
-fn render_header(path: &Path, mode: &Mode, input: &[char], cursor: usize) -> Paragraph<'static> {
+fn render_header(app: &App) -> Paragraph<'static> {
     let theme = THEME;
+    let (path, mode, input, cursor) = (&app.current_dir, &app.mode, &app.input[..], app.cursor);
     let input_str: String = input.iter().collect();
     let (content, accent, label) = match mode {
-        Mode::Search => (format!("> {}", input_str), Color::Yellow, "Search"),
+        Mode::Search => (
+            format!("[{}] > {}", app.filter_mode.label(), input_str),
+            Color::Yellow,
+            "Search",
+        ),
         Mode::Rename => {
             let before: String = input.iter().take(cursor).collect();
             let after: String = input.iter().skip(cursor).collect();
             (format!("{}|{}", before, after), Color::Green, "Rename")
         }
+        Mode::Jump => {
+            let before: String = input.iter().take(cursor).collect();
+            let after: String = input.iter().skip(cursor).collect();
+            (format!("{}|{}", before, after), Color::Magenta, "Jump")
+        }
         Mode::Path => {
             let before: String = input.iter().take(cursor).collect();
             let after: String = input.iter().skip(cursor).collect();
             (format!("{}|{}", before, after), Color::Magenta, "Path")
         }
+        Mode::Find => {
+            let before: String = input.iter().take(cursor).collect();
+            let after: String = input.iter().skip(cursor).collect();
+            (format!("{}|{}", before, after), Color::Yellow, "Find")
+        }
+        Mode::Grep => {
+            let before: String = input.iter().take(cursor).collect();
+            let after: String = input.iter().skip(cursor).collect();
+            (format!("{}|{}", before, after), Color::Yellow, "Grep")
+        }
         Mode::NewFile => {
             let before: String = input.iter().take(cursor).collect();
             let after: String = input.iter().skip(cursor).collect();
@@ -215,29 +253,95 @@ fn render_header(path: &Path, mode: &Mode, input: &[char], cursor: usize) -> Par
             Color::Red,
             "Confirm Delete",
         ),
+        Mode::ConfirmPermanentDelete => (
+            path.to_string_lossy().to_string(),
+            Color::Red,
+            "Permanent Delete",
+        ),
+        Mode::ConflictRename => {
+            let before: String = input.iter().take(cursor).collect();
+            let after: String = input.iter().skip(cursor).collect();
+            (format!("{}|{}", before, after), theme.warning, "Paste As")
+        }
+        Mode::Conflict => (path.to_string_lossy().to_string(), theme.warning, "Conflict"),
+        Mode::PatternRename => {
+            let before: String = input.iter().take(cursor).collect();
+            let after: String = input.iter().skip(cursor).collect();
+            (format!("{}|{}", before, after), theme.warning, "Pattern Rename")
+        }
+        Mode::ConfirmBulkRename => (
+            path.to_string_lossy().to_string(),
+            theme.warning,
+            "Bulk Rename",
+        ),
+        Mode::CopyOptions => (
+            "Applies to pasted copies".to_string(),
+            theme.accent_alt,
+            "Copy Options",
+        ),
+        Mode::ConfirmUndo => (path.to_string_lossy().to_string(), theme.warning, "Undo"),
+        Mode::ConfirmRedo => (path.to_string_lossy().to_string(), theme.warning, "Redo"),
+        Mode::Sort => (path.to_string_lossy().to_string(), theme.accent_alt, "Sort"),
+        Mode::BookmarkSet => (path.to_string_lossy().to_string(), theme.accent_alt, "Mark"),
+        Mode::BookmarkJump => (path.to_string_lossy().to_string(), theme.accent_alt, "Jump"),
+        Mode::Bookmarks => ("Saved directories".to_string(), theme.accent_alt, "Bookmarks"),
+        Mode::Recent => (
+            "Recently visited directories".to_string(),
+            theme.accent_alt,
+            "Recent",
+        ),
+        Mode::Trash | Mode::ConfirmPurge => {
+            ("Deleted items".to_string(), theme.accent_alt, "Trash")
+        }
         Mode::Normal | Mode::Help => (path.to_string_lossy().to_string(), theme.accent, "Path"),
     };
 
-    let spans = vec![
-        badge("fylins", Color::Black, theme.accent),
-        Span::raw(" "),
+    let mut spans = vec![badge("fylins", Color::Black, theme.accent), Span::raw(" ")];
+    // Tab bar, shown once there is more than one tab
+    let tabs = app.tab_titles();
+    let active_tab = app.active_tab;
+    if tabs.len() > 1 {
+        for (i, title) in tabs.iter().enumerate() {
+            let text = format!("{} {}", i + 1, title);
+            if i == active_tab {
+                spans.push(badge(text, Color::Black, theme.accent_alt));
+            } else {
+                spans.push(Span::styled(format!(" {} ", text), Style::default().fg(theme.muted)));
+            }
+        }
+        spans.push(Span::raw(" "));
+    }
+    spans.extend([
         badge(label, Color::Black, accent),
         Span::raw("  "),
         Span::styled(content, Style::default().fg(theme.text)),
-    ];
+    ]);
+    // Attribute filter chips
+    for attribute in &app.attribute_filters {
+        spans.push(Span::raw(" "));
+        spans.push(badge(attribute.text.clone(), Color::Black, theme.warning));
+    }
 
     Paragraph::new(Line::from(spans))
         .style(Style::default().fg(theme.text).bg(theme.surface_alt))
         .block(themed_block("Path", accent))
 }
 
-fn render_preview(preview: &Preview, scroll: u16, width: usize) -> Paragraph<'static> {
+fn render_preview(
+    preview: &Preview,
+    scroll: u16,
+    width: usize,
+    highlight: Option<&(usize, Range<usize>)>,
+) -> Paragraph<'static> {
     let theme = THEME;
     match preview {
         Preview::None => Paragraph::new("Select something to preview")
             .style(Style::default().fg(theme.muted))
             .block(themed_block("Preview", theme.accent))
             .wrap(Wrap { trim: false }),
+        Preview::Loading => Paragraph::new("Loading…")
+            .style(Style::default().fg(theme.muted))
+            .block(themed_block("Preview", theme.accent)),
         Preview::Directory(items) => {
             let content = if items.is_empty() {
                 "[ empty directory ]".to_string()
@@ -250,9 +354,31 @@ fn render_preview(preview: &Preview, scroll: u16, width: usize) -> Paragraph<'st
                 .wrap(Wrap { trim: false })
                 .scroll((scroll, 0))
         }
-        Preview::Text { content, extension } => {
+        Preview::Text {
+            content,
+            extension,
+            lines,
+        } => {
             let title = format_preview_title(extension);
-            let lines = highlight_code(content, extension);
+            let mut lines = lines.clone();
+            // Mark a content search match on top of the syntax colors
+            if let Some((index, range)) = highlight {
+                let text = content.lines().nth(*index).unwrap_or("");
+                let matched = text.get(range.clone());
+                if let (Some(line), Some(matched)) = (lines.get_mut(*index), matched) {
+                    *line = Line::from(vec![
+                        Span::raw(text[..range.start].to_string()),
+                        Span::styled(
+                            matched.to_string(),
+                            Style::default()
+                                .fg(Color::Black)
+                                .bg(theme.warning)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw(text[range.end..].to_string()),
+                    ]);
+                }
+            }
             Paragraph::new(lines)
                 .style(Style::default().fg(theme.text))
                 .block(themed_block(title, theme.accent))
@@ -341,16 +467,94 @@ fn render_help(mode: &Mode) -> Paragraph<'static> {
     let hints: Vec<(&str, &str)> = match mode {
         Mode::Normal => vec![
             ("hjkl", "move"),
+            ("space", "select"),
             ("c/x/v", "copy/cut/paste"),
-            ("d", "delete"),
+            ("d", "trash"),
+            ("u/U", "undo/redo"),
             ("n/N", "new"),
             ("q", "quit"),
             ("?", "help"),
         ],
-        Mode::Path => vec![("Enter", "go"), ("Esc", "cancel")],
-        Mode::Search => vec![("Enter", "confirm"), ("Esc", "cancel")],
+        Mode::Jump => vec![
+            ("Up/Down", "choose"),
+            ("Tab", "edit path"),
+            ("Enter", "go"),
+            ("Esc", "cancel"),
+        ],
+        Mode::Grep => vec![
+            ("Enter", "search / open result"),
+            ("Up/Down", "choose"),
+            ("Esc", "close"),
+        ],
+        Mode::Find => vec![
+            ("Up/Down", "choose"),
+            ("Enter", "go to file"),
+            ("Esc", "cancel"),
+        ],
+        Mode::Path => vec![
+            ("Tab", "complete"),
+            ("Up/Down", "choose"),
+            ("~ $VAR", "expand"),
+            ("Enter", "go"),
+            ("Esc", "cancel"),
+        ],
+        Mode::Search => vec![("Tab", "match mode"), ("Enter", "confirm"), ("Esc", "cancel")],
         Mode::Rename => vec![("Enter", "confirm"), ("Esc", "cancel")],
-        Mode::ConfirmDelete => vec![("y", "delete"), ("n/Esc", "cancel")],
+        Mode::ConfirmDelete => vec![("y", "trash"), ("n/Esc", "cancel")],
+        Mode::ConfirmBulkRename => vec![("y", "rename all"), ("n/Esc", "cancel")],
+        Mode::PatternRename => vec![
+            ("$1", "capture"),
+            ("{n:03}", "counter"),
+            ("upper/lower/title", "case"),
+            ("ext=", "extension"),
+            ("Enter", "rename"),
+            ("Esc", "cancel"),
+        ],
+        Mode::ConfirmUndo => vec![("y", "undo"), ("n/Esc", "cancel")],
+        Mode::ConfirmRedo => vec![("y", "redo"), ("n/Esc", "cancel")],
+        Mode::ConfirmPermanentDelete | Mode::ConfirmPurge => {
+            vec![("Y", "delete forever"), ("any", "cancel")]
+        }
+        Mode::Recent => vec![("jk", "move"), ("Enter", "jump"), ("Esc", "close")],
+        Mode::Sort => vec![
+            ("n/N", "name/natural"),
+            ("s/m", "size/date"),
+            ("e/g", "ext/git"),
+            ("r", "reverse"),
+            ("d", "dirs first"),
+            ("p", "per dir"),
+            ("Esc", "cancel"),
+        ],
+        Mode::BookmarkSet => vec![("a-z/0-9", "save bookmark"), ("Esc", "cancel")],
+        Mode::BookmarkJump => vec![("key", "jump"), ("'", "previous dir"), ("Esc", "cancel")],
+        Mode::Bookmarks => vec![
+            ("jk", "move"),
+            ("Enter", "jump"),
+            ("d", "delete"),
+            ("Esc", "close"),
+        ],
+        Mode::Trash => vec![
+            ("jk", "move"),
+            ("Enter/r", "restore"),
+            ("D", "delete forever"),
+            ("Esc", "close"),
+        ],
+        Mode::Conflict => vec![
+            ("o", "overwrite"),
+            ("s", "skip"),
+            ("r", "rename"),
+            ("n", "keep newer"),
+            ("a", "auto-suffix"),
+            ("space", "apply to all"),
+            ("Esc", "cancel paste"),
+        ],
+        Mode::ConflictRename => vec![("Enter", "paste as"), ("Esc", "back")],
+        Mode::CopyOptions => vec![
+            ("t", "timestamps"),
+            ("m", "permissions"),
+            ("l", "symlinks"),
+            ("Esc", "close"),
+        ],
         Mode::NewFile | Mode::NewFolder => vec![("Enter", "create"), ("Esc", "cancel")],
         Mode::Help => vec![("?", "close"), ("Esc", "close")],
     };
@@ -395,11 +599,53 @@ fn render_help_screen<'a>() -> Paragraph<'a> {
             ("l or Enter", "Open or enter"),
             ("h or Backspace", "Parent directory"),
             ("`", "Go to start directory"),
+            ("[ / ] or Alt+left/right", "Back / Forward in history"),
+            ("g", "Recent directories"),
+            ("m<key> / '<key>", "Set / Jump to bookmark"),
+            ("''", "Jump to previous directory"),
+            ("B", "Manage bookmarks"),
             ("PgUp/PgDn", "Scroll preview"),
         ],
         &theme,
     );
 
+    push_help_section(
+        &mut lines,
+        "Tabs",
+        &[
+            ("t", "New tab in this directory"),
+            ("w", "Close tab"),
+            ("Tab / Shift+Tab", "Next / Previous tab"),
+            ("1-9", "Go to tab"),
+        ],
+        &theme,
+    );
+
+    push_help_section(
+        &mut lines,
+        "Layout",
+        &[
+            ("s", "Toggle two file panels"),
+            ("M", "Toggle Miller columns (parent / files / preview)"),
+            ("Tab", "Switch panel"),
+            ("c or F5", "Copy to the other panel"),
+            ("x or F6", "Move to the other panel"),
+        ],
+        &theme,
+    );
+
+    push_help_section(
+        &mut lines,
+        "Selection",
+        &[
+            ("Space", "Toggle selection and move down"),
+            ("a", "Select all"),
+            ("i", "Invert selection"),
+            ("Esc", "Clear selection"),
+        ],
+        &theme,
+    );
+
     push_help_section(
         &mut lines,
         "File actions",
@@ -407,20 +653,43 @@ fn render_help_screen<'a>() -> Paragraph<'a> {
             ("c / x / v", "Copy / Cut / Paste"),
             ("n / N", "New file / folder"),
             ("r", "Rename"),
-            ("d", "Delete"),
+            ("E", "Bulk rename in $EDITOR"),
+            ("R", "Pattern rename (find/replace/options)"),
+            ("d", "Move to trash"),
+            ("D", "Delete permanently"),
+            ("T", "Browse trash (restore items)"),
+            ("u / U", "Undo / Redo last file operation"),
+            ("P / X", "Pause/resume / Cancel background transfer"),
+            ("C", "Copy options (timestamps, permissions, symlinks)"),
             ("o", "Open with default app"),
             ("y", "Copy path to clipboard"),
         ],
         &theme,
     );
 
+    push_help_section(
+        &mut lines,
+        "Paste conflicts",
+        &[
+            ("o / s", "Overwrite / Skip"),
+            ("r", "Paste under a new name"),
+            ("n", "Keep the newer item"),
+            ("a", "Auto-suffix the name"),
+            ("Space", "Apply choice to all conflicts"),
+        ],
+        &theme,
+    );
+
     push_help_section(
         &mut lines,
         "View & filter",
         &[
             ("/", "Search or filter"),
             ("H", "Toggle hidden files"),
-            ("p", "Jump to path"),
+            ("S", "Sort by name, natural order, size, date, extension or git"),
+            ("p", "Jump to frequent directory"),
+            ("f", "Find files below this directory"),
+            ("G", "Search file contents below this directory"),
         ],
         &theme,
     );
@@ -458,6 +727,7 @@ fn render_help_screen<'a>() -> Paragraph<'a> {
 
 /// Renders the complete UI to the terminal frame.
 pub fn draw_ui(f: &mut Frame, app: &mut App) {
+    let theme = THEME;
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -477,13 +747,17 @@ pub fn draw_ui(f: &mut Frame, app: &mut App) {
 
     // Collect entry data to avoid borrow conflicts
     let entry_data: Vec<EntryDisplay> = app
-        .entries()
-        .map(|e| EntryDisplay {
+        .filtered_indices
+        .iter()
+        .zip(app.entries())
+        .map(|(i, e)| EntryDisplay {
             name: e.name.clone(),
             is_dir: e.is_dir,
             size: e.size,
             is_hidden: e.is_hidden,
             git_status: e.git_status,
+            selected: app.is_selected(e),
+            matched: app.filter_positions.get(i).cloned().unwrap_or_default(),
         })
         .collect();
 
@@ -498,7 +772,7 @@ pub fn draw_ui(f: &mut Frame, app: &mut App) {
     });
 
     // Build widgets
-    let header = render_header(&app.current_dir, &app.mode, &app.input[..], app.cursor);
+    let header = render_header(app);
     let help = render_help(&app.mode);
 
     // If in help mode, show help screen instead of file list and preview
@@ -507,19 +781,227 @@ pub fn draw_ui(f: &mut Frame, app: &mut App) {
         f.render_widget(header, main_chunks[0]);
         f.render_widget(help_screen, main_chunks[1]);
         f.render_widget(help, main_chunks[3]);
+    } else if let (Mode::Conflict | Mode::ConflictRename, Some(plan)) =
+        (&app.mode, &app.pending_paste)
+    {
+        let panel = render_conflict(plan);
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_widget(panel, main_chunks[1]);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
+    } else if app.mode == Mode::CopyOptions {
+        let panel = render_copy_options(&app.copy_policy);
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_widget(panel, main_chunks[1]);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
+    } else if app.mode == Mode::Bookmarks {
+        let bookmark_list = render_bookmark_list(&app.bookmarks, &app.current_dir);
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_stateful_widget(bookmark_list, main_chunks[1], &mut app.bookmark_state);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
+    } else if app.mode == Mode::Find {
+        let find_list = render_find_results(app);
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_stateful_widget(find_list, main_chunks[1], &mut app.find_state);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
+    } else if app.mode == Mode::Grep {
+        let grep_list = render_grep_results(app.grep.as_ref());
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_stateful_widget(grep_list, main_chunks[1], &mut app.grep_state);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
+    } else if app.mode == Mode::Recent {
+        let recent_list = render_recent_list(app.history.recent(), &app.current_dir);
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_stateful_widget(recent_list, main_chunks[1], &mut app.recent_state);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
+    } else if matches!(app.mode, Mode::Trash | Mode::ConfirmPurge) {
+        let trash_list = render_trash_list(&app.trash_items);
+        let status = render_status_bar_data(&app.message, &app.mode, None, 0, None, 0);
+        f.render_widget(header, main_chunks[0]);
+        f.render_stateful_widget(trash_list, main_chunks[1], &mut app.trash_state);
+        f.render_widget(status, main_chunks[2]);
+        f.render_widget(help, main_chunks[3]);
     } else {
-        let file_list = render_file_list_owned(&entry_data, app.show_hidden);
-        let preview = render_preview(&app.preview, app.scroll, preview_width);
-        let status = render_status_bar_data(&app.message, &app.mode, status_info.as_ref());
+        let file_list = render_file_list_owned(
+            &entry_data,
+            app.show_hidden,
+            app.selection.len(),
+            &app.sort_for(&app.current_dir).label(),
+        );
+        let preview = match (&app.mode, &app.rename_plan) {
+            (Mode::ConfirmBulkRename, Some(plan)) => {
+                render_rename_preview(&plan.renames, &[], app.scroll)
+            }
+            (Mode::PatternRename, _) => {
+                render_rename_preview(&app.pattern_preview, &app.pattern_problems, app.scroll)
+            }
+            _ => render_preview(
+                &app.preview,
+                app.scroll,
+                preview_width,
+                app.preview_match.as_ref(),
+            ),
+        };
+        let transfer = app.transfers.progress();
+        let status = render_status_bar_data(
+            &app.message,
+            &app.mode,
+            status_info.as_ref(),
+            app.selection.len(),
+            transfer.as_ref(),
+            app.transfers.queued(),
+        );
+
+        // In dual-pane mode the other panel takes the preview's place unless
+        // a prompt needs that space
+        let side_panel = matches!(
+            app.mode,
+            Mode::Jump | Mode::Path | Mode::ConfirmBulkRename | Mode::PatternRename
+        );
+        let other_pane = app.other_pane().filter(|_| !side_panel).map(|pane| {
+            let data: Vec<EntryDisplay> = pane
+                .entries()
+                .map(|e| EntryDisplay {
+                    name: e.name.clone(),
+                    is_dir: e.is_dir,
+                    size: e.size,
+                    is_hidden: e.is_hidden,
+                    git_status: e.git_status,
+                    selected: pane.is_selected(e),
+                    matched: Vec::new(),
+                })
+                .collect();
+            let sort = app.sort_for(&pane.current_dir).label();
+            let list = render_file_list_owned(&data, app.show_hidden, pane.selection_len(), &sort)
+                .block(themed_block(pane.current_dir.to_string_lossy().to_string(), theme.muted));
+            (list, ListState::default().with_selected(pane.selected()))
+        });
 
         f.render_widget(header, main_chunks[0]);
-        f.render_stateful_widget(file_list, content_chunks[0], &mut app.state);
-        f.render_widget(preview, content_chunks[1]);
+        if let Some((other_list, mut other_state)) = other_pane {
+            let panels = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(main_chunks[1]);
+            let (active_area, other_area) = if app.focus_right {
+                (panels[1], panels[0])
+            } else {
+                (panels[0], panels[1])
+            };
+            let mut title = app.current_dir.to_string_lossy().to_string();
+            if !app.selection.is_empty() {
+                title.push_str(&format!(" [{} selected]", app.selection.len()));
+            }
+            let file_list = file_list.block(themed_block(title, theme.accent));
+            f.render_stateful_widget(file_list, active_area, &mut app.state);
+            f.render_stateful_widget(other_list, other_area, &mut other_state);
+        } else {
+            // Miller columns put the parent listing left of the file list
+            let content_chunks = if app.miller_columns {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(35),
+                        Constraint::Percentage(45),
+                    ])
+                    .split(main_chunks[1]);
+                let (parent_list, mut parent_state) =
+                    render_parent_column(app.parent_column.as_ref(), &app.current_dir);
+                f.render_stateful_widget(parent_list, columns[0], &mut parent_state);
+                columns[1..].to_vec()
+            } else {
+                content_chunks.to_vec()
+            };
+            f.render_stateful_widget(file_list, content_chunks[0], &mut app.state);
+            if app.mode == Mode::Jump {
+                let dirs = app.jump_matches.iter().map(|d| d.to_string_lossy().to_string());
+                let jump_list = render_suggestion_list(
+                    "Frequent Directories",
+                    dirs.collect(),
+                    "No matching directory",
+                );
+                f.render_stateful_widget(jump_list, content_chunks[1], &mut app.jump_state);
+            } else if app.mode == Mode::Path {
+                let path_list = render_suggestion_list(
+                    "Directories",
+                    app.path_suggestions.clone(),
+                    "No matching directory",
+                );
+                f.render_stateful_widget(path_list, content_chunks[1], &mut app.path_state);
+            } else {
+                f.render_widget(preview, content_chunks[1]);
+            }
+        }
         f.render_widget(status, main_chunks[2]);
         f.render_widget(help, main_chunks[3]);
     }
 }
 
+/// Parent directory listing with the current directory highlighted.
+fn render_parent_column(
+    column: Option<&ParentColumn>,
+    current: &Path,
+) -> (List<'static>, ListState) {
+    let theme = THEME;
+    let Some(column) = column else {
+        return (
+            List::new(Vec::<ListItem>::new()).block(themed_block("/", theme.muted)),
+            ListState::default(),
+        );
+    };
+
+    let current_name = current.file_name().map(|n| n.to_string_lossy().to_string());
+    let selected = column
+        .entries
+        .iter()
+        .position(|(_, name)| Some(name) == current_name.as_ref());
+    let items: Vec<ListItem> = column
+        .entries
+        .iter()
+        .map(|(is_dir, name)| {
+            let (icon, style) = if *is_dir {
+                (
+                    Span::styled("> ", Style::default().fg(theme.accent)),
+                    Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (
+                    Span::styled("- ", Style::default().fg(theme.muted)),
+                    Style::default().fg(theme.muted),
+                )
+            };
+            ListItem::new(Line::from(vec![icon, Span::styled(name.clone(), style)]))
+        })
+        .collect();
+
+    let title = column
+        .dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| column.dir.to_string_lossy().to_string());
+    let list = List::new(items)
+        .block(themed_block(title, theme.muted))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD),
+        );
+    (list, ListState::default().with_selected(selected))
+}
+
 // Helper structs for owned data
 struct EntryDisplay {
     name: String,
@@ -527,6 +1009,9 @@ struct EntryDisplay {
     size: u64,
     is_hidden: bool,
     git_status: Option<GitStatus>,
+    selected: bool,
+    /// Char indices of the name matched by the filter
+    matched: Vec<usize>,
 }
 
 struct StatusInfo {
@@ -538,12 +1023,24 @@ struct StatusInfo {
     readonly: bool,
 }
 
-fn render_file_list_owned(entries: &[EntryDisplay], show_hidden: bool) -> List<'static> {
+fn render_file_list_owned(
+    entries: &[EntryDisplay],
+    show_hidden: bool,
+    selected_count: usize,
+    sort_label: &str,
+) -> List<'static> {
     let theme = THEME;
     let items: Vec<ListItem> = entries
         .iter()
         .map(|entry| {
-            let icon = if entry.is_dir {
+            let icon = if entry.selected {
+                Span::styled(
+                    "* ",
+                    Style::default()
+                        .fg(theme.warning)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else if entry.is_dir {
                 Span::styled("> ", Style::default().fg(theme.accent))
             } else {
                 Span::styled("- ", Style::default().fg(theme.muted))
@@ -560,6 +1057,11 @@ fn render_file_list_owned(entries: &[EntryDisplay], show_hidden: bool) -> List<'
             } else {
                 base_style
             };
+            let name_style = if entry.selected {
+                name_style.fg(theme.warning)
+            } else {
+                name_style
+            };
 
             let git_indicator = match entry.git_status {
                 Some(GitStatus::Modified) => Some(badge("M", Color::Black, Color::Yellow)),
@@ -570,7 +1072,9 @@ fn render_file_list_owned(entries: &[EntryDisplay], show_hidden: bool) -> List<'
                 None => None,
             };
 
-            let mut spans = vec![icon, Span::styled(entry.name.clone(), name_style)];
+            let matched_style = name_style.fg(theme.warning).add_modifier(Modifier::UNDERLINED);
+            let mut spans = vec![icon];
+            spans.extend(highlight_spans(&entry.name, &entry.matched, name_style, matched_style));
             if let Some(badge) = git_indicator {
                 spans.push(Span::raw(" "));
                 spans.push(badge);
@@ -587,11 +1091,13 @@ fn render_file_list_owned(entries: &[EntryDisplay], show_hidden: bool) -> List<'
         })
         .collect();
 
-    let title = if show_hidden {
-        "Files (showing hidden)"
-    } else {
-        "Files"
-    };
+    let mut title = format!("Files [{}]", sort_label);
+    if show_hidden {
+        title.push_str(" (showing hidden)");
+    }
+    if selected_count > 0 {
+        title.push_str(&format!(" [{} selected]", selected_count));
+    }
 
     List::new(items)
         .block(themed_block(title, theme.accent))
@@ -604,27 +1110,498 @@ fn render_file_list_owned(entries: &[EntryDisplay], show_hidden: bool) -> List<'
         .highlight_symbol("> ")
 }
 
+/// Bookmark manager, laid out like the help screen: one key badge per row.
+fn render_bookmark_list(bookmarks: &Bookmarks, current: &Path) -> List<'static> {
+    let theme = THEME;
+    let items: Vec<ListItem> = bookmarks
+        .iter()
+        .map(|(key, dir)| {
+            let mut line = help_row(&key.to_string(), &dir.to_string_lossy(), &theme);
+            if dir == current {
+                line.spans
+                    .push(Span::styled("  (here)", Style::default().fg(theme.muted)));
+            }
+            ListItem::new(line)
+        })
+        .collect();
+
+    List::new(items)
+        .block(themed_block(
+            format!("Bookmarks ({})", bookmarks.len()),
+            theme.accent_alt,
+        ))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ")
+}
+
+fn render_recent_list(dirs: &[PathBuf], current: &Path) -> List<'static> {
+    let theme = THEME;
+    let items: Vec<ListItem> = dirs
+        .iter()
+        .map(|dir| {
+            let (icon, style) = if dir == current {
+                ("* ", Style::default().fg(theme.warning))
+            } else {
+                ("  ", Style::default().fg(theme.text))
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(icon, Style::default().fg(theme.accent)),
+                Span::styled(dir.to_string_lossy().to_string(), style),
+            ]))
+        })
+        .collect();
+
+    List::new(items)
+        .block(themed_block(
+            format!("Recent Directories ({})", dirs.len()),
+            theme.accent_alt,
+        ))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ")
+}
+
+/// Content search results as `path:line: snippet`.
+fn render_grep_results(search: Option<&ContentSearch>) -> List<'static> {
+    let theme = THEME;
+    let Some(search) = search else {
+        return List::new(vec![ListItem::new(Span::styled(
+            "Type the text to search for and press Enter",
+            Style::default().fg(theme.muted),
+        ))])
+        .block(themed_block("Grep", theme.accent_alt));
+    };
+
+    let matcher = Matcher::new(&search.query);
+    let items: Vec<ListItem> = search
+        .matches
+        .iter()
+        .map(|m| {
+            let mut spans = vec![
+                Span::styled(m.path.clone(), Style::default().fg(theme.accent)),
+                Span::styled(format!(":{}: ", m.line), Style::default().fg(theme.muted)),
+            ];
+            match matcher.find(&m.snippet) {
+                Some(range) => {
+                    spans.push(Span::raw(m.snippet[..range.start].to_string()));
+                    spans.push(Span::styled(
+                        m.snippet[range.clone()].to_string(),
+                        Style::default()
+                            .fg(theme.warning)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::raw(m.snippet[range.end..].to_string()));
+                }
+                None => spans.push(Span::raw(m.snippet.clone())),
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = format!(
+        "Grep '{}' in {} ({} matches{})",
+        search.query,
+        search.root.display(),
+        search.matches.len(),
+        if search.is_done() { "" } else { ", searching" }
+    );
+    List::new(items)
+        .block(themed_block(title, theme.accent_alt))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ")
+}
+
+/// Splits `text` into spans, styling the chars at the sorted `positions`
+/// with `matched` and the rest with `plain`.
+fn highlight_spans(
+    text: &str,
+    positions: &[usize],
+    plain: Style,
+    matched: Style,
+) -> Vec<Span<'static>> {
+    // Group runs of matched / unmatched characters into spans
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    let mut positions = positions.iter().peekable();
+    for (i, c) in text.chars().enumerate() {
+        let is_match = positions.next_if(|&&p| p == i).is_some();
+        if is_match != run_matched && !run.is_empty() {
+            let style = if run_matched { matched } else { plain };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    spans.push(Span::styled(run, if run_matched { matched } else { plain }));
+    spans
+}
+
+/// Finder results with the matched characters highlighted.
+fn render_find_results(app: &App) -> List<'static> {
+    let theme = THEME;
+    let items: Vec<ListItem> = app
+        .find_results
+        .iter()
+        .map(|result| {
+            let matched = Style::default()
+                .fg(theme.warning)
+                .add_modifier(Modifier::BOLD);
+            let plain = Style::default().fg(theme.text);
+            let spans = highlight_spans(&result.path, &result.positions, plain, matched);
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = match &app.finder {
+        Some(finder) => format!(
+            "Find in {} ({} of {}{})",
+            finder.root.display(),
+            app.find_results.len(),
+            finder.len(),
+            if finder.is_done() { "" } else { ", scanning" }
+        ),
+        None => "Find".to_string(),
+    };
+    List::new(items)
+        .block(themed_block(title, theme.accent_alt))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ")
+}
+
+/// Dropdown shown in place of the preview while a prompt offers choices.
+fn render_suggestion_list(title: &str, items: Vec<String>, empty: &'static str) -> List<'static> {
+    let theme = THEME;
+    let count = items.len();
+    let list_items: Vec<ListItem> = if items.is_empty() {
+        vec![ListItem::new(Span::styled(empty, Style::default().fg(theme.muted)))]
+    } else {
+        items
+            .into_iter()
+            .map(|item| ListItem::new(Span::styled(item, Style::default().fg(theme.text))))
+            .collect()
+    };
+
+    List::new(list_items)
+        .block(themed_block(format!("{} ({})", title, count), theme.accent_alt))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ")
+}
+
+fn render_trash_list(items: &[TrashItem]) -> List<'static> {
+    let theme = THEME;
+    let list_items: Vec<ListItem> = items
+        .iter()
+        .map(|item| {
+            let icon = if item.is_dir {
+                Span::styled("> ", Style::default().fg(theme.accent))
+            } else {
+                Span::styled("- ", Style::default().fg(theme.muted))
+            };
+            let origin = item
+                .original_path
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                icon,
+                Span::styled(item.name(), Style::default().fg(theme.text)),
+                Span::raw("  "),
+                Span::styled(
+                    item.deletion_date.replace('T', " "),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::raw("  "),
+                Span::styled(origin, Style::default().fg(theme.muted)),
+            ]))
+        })
+        .collect();
+
+    List::new(list_items)
+        .block(themed_block(
+            format!("Trash ({} items)", items.len()),
+            theme.accent_alt,
+        ))
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ")
+}
+
+/// Side-by-side comparison of the pasted item and the one it collides with.
+fn render_conflict(plan: &PastePlan) -> Paragraph<'static> {
+    let theme = THEME;
+    let Some(conflict) = &plan.conflict else {
+        return Paragraph::new("");
+    };
+    let highlight = Style::default()
+        .fg(theme.warning)
+        .add_modifier(Modifier::BOLD);
+    let plain = Style::default().fg(theme.text);
+    let kind = |is_dir: bool| if is_dir { "Folder" } else { "File" };
+
+    let source_newer = conflict.source_is_newer();
+    let existing_newer = match (conflict.source.modified, conflict.existing.modified) {
+        (Some(src), Some(existing)) => existing > src,
+        (None, Some(_)) => true,
+        _ => false,
+    };
+    let rows = [
+        (
+            "Type",
+            kind(conflict.source.is_dir).to_string(),
+            kind(conflict.existing.is_dir).to_string(),
+            false,
+            false,
+        ),
+        (
+            "Size",
+            format_size(conflict.source.size),
+            format_size(conflict.existing.size),
+            conflict.source.size > conflict.existing.size,
+            conflict.existing.size > conflict.source.size,
+        ),
+        (
+            "Modified",
+            format_time(conflict.source.modified),
+            format_time(conflict.existing.modified),
+            source_newer,
+            existing_newer,
+        ),
+    ];
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("'", plain),
+            Span::styled(
+                display_file_name(&conflict.dest),
+                plain.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("' already exists in ", plain),
+            Span::styled(
+                plan.dir.to_string_lossy().to_string(),
+                Style::default().fg(theme.accent),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(format!("  {:<10}", ""), plain),
+            Span::styled(format!("{:<24}", "Pasted"), Style::default().fg(theme.accent)),
+            Span::styled("Existing", Style::default().fg(theme.accent_alt)),
+        ]),
+    ];
+    for (label, source, existing, source_wins, existing_wins) in rows {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<10}", label), Style::default().fg(theme.muted)),
+            Span::styled(
+                format!("{:<24}", source),
+                if source_wins { highlight } else { plain },
+            ),
+            Span::styled(existing, if existing_wins { highlight } else { plain }),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("  From  ", Style::default().fg(theme.muted)),
+        Span::styled(conflict.src.to_string_lossy().to_string(), plain),
+    ]));
+    lines.push(Line::from(""));
+
+    let apply_all = if plan.apply_to_all {
+        badge("APPLY TO ALL", Color::Black, theme.warning)
+    } else {
+        Span::styled("[ ] apply to all", Style::default().fg(theme.muted))
+    };
+    lines.push(Line::from(vec![
+        Span::raw("  "),
+        apply_all,
+        Span::styled(
+            format!("  {} more item(s) after this", plan.remaining()),
+            Style::default().fg(theme.muted),
+        ),
+    ]));
+
+    Paragraph::new(lines)
+        .block(themed_block("Paste Conflict", theme.warning))
+        .wrap(Wrap { trim: false })
+}
+
+/// Lists planned renames as `old → new`, flagging rows with a problem.
+fn render_rename_preview(
+    renames: &[(String, String)],
+    problems: &[Option<String>],
+    scroll: u16,
+) -> Paragraph<'static> {
+    let theme = THEME;
+    let old_width = renames
+        .iter()
+        .map(|(old, _)| old.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let lines: Vec<Line> = renames
+        .iter()
+        .enumerate()
+        .map(|(i, (old, new))| {
+            let problem = problems.get(i).and_then(|p| p.as_ref());
+            let unchanged = old == new;
+            let new_style = match problem {
+                Some(_) => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                None if unchanged => Style::default().fg(theme.muted),
+                None => Style::default().fg(Color::Green),
+            };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:<width$}", old, width = old_width),
+                    Style::default().fg(if unchanged { theme.muted } else { theme.text }),
+                ),
+                Span::styled(" → ", Style::default().fg(theme.muted)),
+                Span::styled(new.clone(), new_style),
+            ];
+            if let Some(problem) = problem {
+                spans.push(Span::styled(
+                    format!("  ({})", problem),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let changed = renames.iter().filter(|(old, new)| old != new).count();
+    let invalid = problems.iter().filter(|p| p.is_some()).count();
+    let title = if invalid > 0 {
+        format!("Rename Preview ({} changes, {} invalid)", changed, invalid)
+    } else {
+        format!("Rename Preview ({} changes)", changed)
+    };
+
+    Paragraph::new(lines)
+        .block(themed_block(
+            title,
+            if invalid > 0 { Color::Red } else { theme.warning },
+        ))
+        .scroll((scroll, 0))
+}
+
+fn render_copy_options(policy: &CopyPolicy) -> Paragraph<'static> {
+    let theme = THEME;
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let rows = [
+        ("t", "Preserve timestamps", on_off(policy.preserve_timestamps)),
+        ("m", "Preserve permissions", on_off(policy.preserve_permissions)),
+        ("l", "Symlinks", policy.symlinks.label()),
+    ];
+
+    let mut lines = vec![Line::from(Span::styled(
+        "How pasted copies replicate their source (moves always keep everything):",
+        Style::default().fg(theme.muted),
+    ))];
+    lines.push(Line::from(""));
+    for (key, label, value) in rows {
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            badge(key, Color::Black, theme.accent),
+            Span::styled(format!(" {:<22}", label), Style::default().fg(theme.text)),
+            Span::styled(
+                value,
+                Style::default()
+                    .fg(theme.warning)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Defaults come from preserve_timestamps, preserve_permissions and symlinks (link/follow) in the config file.",
+        Style::default().fg(theme.muted),
+    )));
+
+    Paragraph::new(lines)
+        .block(themed_block("Copy Options", theme.accent_alt))
+        .wrap(Wrap { trim: false })
+}
+
+fn display_file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
 fn render_status_bar_data(
     message: &Option<String>,
     mode: &Mode,
     entry: Option<&StatusInfo>,
+    selected_count: usize,
+    transfer: Option<&Progress>,
+    queued: usize,
 ) -> Paragraph<'static> {
     let theme = THEME;
+    let transfer_spans = transfer
+        .map(|p| transfer_progress_spans(p, queued))
+        .unwrap_or_default();
 
     if let Some(msg) = message {
-        let is_delete = *mode == Mode::ConfirmDelete;
+        let is_delete = matches!(
+            mode,
+            Mode::ConfirmDelete | Mode::ConfirmPermanentDelete | Mode::ConfirmPurge
+        );
         let status_badge = if is_delete {
             badge(msg.clone(), Color::White, Color::Red)
         } else {
             badge(msg.clone(), Color::Black, theme.warning)
         };
         let accent = if is_delete { Color::Red } else { theme.accent };
-        return Paragraph::new(Line::from(vec![status_badge]))
-            .block(themed_block("Status", accent));
+        let mut spans = vec![status_badge];
+        if !transfer_spans.is_empty() {
+            spans.push(Span::raw("  "));
+            spans.extend(transfer_spans);
+        }
+        return Paragraph::new(Line::from(spans)).block(themed_block("Status", accent));
     }
 
     let mut spans: Vec<Span> = Vec::new();
 
+    if !transfer_spans.is_empty() {
+        spans.extend(transfer_spans);
+        spans.push(Span::raw("  "));
+    }
+
+    if selected_count > 0 {
+        spans.push(badge(
+            format!("{} selected", selected_count),
+            Color::Black,
+            theme.warning,
+        ));
+        spans.push(Span::raw("  "));
+    }
+
     if let Some(e) = entry {
         if e.name == ".." {
             spans.push(Span::styled(
@@ -674,6 +1651,44 @@ fn render_status_bar_data(
         .block(themed_block("Info", theme.accent))
 }
 
+fn transfer_progress_spans(progress: &Progress, queued: usize) -> Vec<Span<'static>> {
+    let theme = THEME;
+    let label = match progress.kind {
+        TransferKind::Copy => "COPY",
+        TransferKind::Move => "MOVE",
+    };
+    let eta = progress
+        .eta()
+        .map(format_duration)
+        .unwrap_or_else(|| "--:--".to_string());
+    let mut spans = vec![
+        badge(label, Color::Black, theme.accent_alt),
+        Span::styled(
+            format!(
+                " {}/{} files  {}/{}  {}/s  ETA {}",
+                progress.files_done,
+                progress.files_total,
+                format_size(progress.bytes_done),
+                format_size(progress.bytes_total),
+                format_size(progress.throughput() as u64),
+                eta
+            ),
+            Style::default().fg(theme.text),
+        ),
+    ];
+    if progress.paused {
+        spans.push(Span::raw(" "));
+        spans.push(badge("PAUSED", Color::Black, theme.warning));
+    }
+    if queued > 0 {
+        spans.push(Span::styled(
+            format!("  +{} queued", queued),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    spans
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -708,6 +1723,13 @@ mod tests {
         assert_eq!(format_size(2 * 1024 * 1024 * 1024), "2.0G");
     }
 
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0:05");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2:05");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+    }
+
     #[test]
     fn test_is_leap_year() {
         assert!(is_leap_year(2000)); // divisible by 400
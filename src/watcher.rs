@@ -0,0 +1,240 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify::{
+    event::{AccessKind, AccessMode, MetadataKind, ModifyKind},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+
+use crate::events::Message;
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// A burst of events is handled once it has been quiet for this long...
+const QUIET_PERIOD: Duration = Duration::from_millis(150);
+
+/// ...or once it has lasted this long, so a busy directory still updates
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// How long the thread waits for an event while no burst is open
+const IDLE_WAIT: Duration = Duration::from_secs(60);
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Watches the current directory and the previewed path for changes made
+/// by other programs (inotify on Linux). A thread collects each burst of
+/// events and posts what it touched as `Message::FilesChanged`.
+pub struct DirWatcher {
+    watcher: RecommendedWatcher,
+    /// Paths the thread checks events against
+    watched: Arc<Mutex<Watched>>,
+    dir: Option<PathBuf>,
+    preview: Option<PathBuf>,
+}
+
+#[derive(Default)]
+struct Watched {
+    dir: Option<PathBuf>,
+    preview: Option<PathBuf>,
+}
+
+/// Events collected since the first one of a burst.
+struct Burst {
+    dir: PathBuf,
+    start: Instant,
+    last: Instant,
+    changes: Changes,
+}
+
+/// What a burst of events touched.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Changes {
+    /// Entries of the watched directory were added, removed or changed.
+    pub listing: bool,
+    /// The previewed file, or the contents of the previewed directory.
+    pub preview: bool,
+}
+
+// =============================================================================
+// DirWatcher Implementation
+// =============================================================================
+
+impl DirWatcher {
+    pub fn new(sender: Sender<Message>) -> notify::Result<DirWatcher> {
+        let (events, receiver) = mpsc::channel();
+        let watched = Arc::new(Mutex::new(Watched::default()));
+        let paths = Arc::clone(&watched);
+        let watcher = notify::recommended_watcher(events)?;
+        // Ends once the watcher, and with it the event sender, is dropped
+        thread::spawn(move || collect_bursts(receiver, &paths, &sender));
+        Ok(DirWatcher {
+            watcher,
+            watched,
+            dir: None,
+            preview: None,
+        })
+    }
+
+    /// Watches `dir` and `preview` instead of the previous paths. Cheap when
+    /// nothing changed; paths that cannot be watched are not retried.
+    pub fn watch(&mut self, dir: &Path, preview: Option<&Path>) {
+        {
+            let mut watched = self.watched.lock().unwrap_or_else(|e| e.into_inner());
+            if watched.dir.as_deref() != Some(dir) {
+                watched.dir = Some(dir.to_path_buf());
+            }
+            if watched.preview.as_deref() != preview {
+                watched.preview = preview.map(Path::to_path_buf);
+            }
+        }
+
+        if self.dir.as_deref() != Some(dir) {
+            if let Some(old) = self.dir.take() {
+                let _ = self.watcher.unwatch(&old);
+            }
+            let _ = self.watcher.watch(dir, RecursiveMode::NonRecursive);
+            self.dir = Some(dir.to_path_buf());
+        }
+
+        // A file in the directory is already covered by the directory watch
+        let preview = preview.filter(|p| p.parent() != Some(dir) || p.is_dir());
+        if self.preview.as_deref() != preview {
+            if let Some(old) = self.preview.take() {
+                if self.dir.as_ref() != Some(&old) {
+                    let _ = self.watcher.unwatch(&old);
+                }
+            }
+            if let Some(path) = preview {
+                if self.dir.as_deref() != Some(path) {
+                    let _ = self.watcher.watch(path, RecursiveMode::NonRecursive);
+                }
+                self.preview = Some(path.to_path_buf());
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Event Thread
+// =============================================================================
+
+/// Sorts incoming events into bursts and posts what each one changed,
+/// until either channel closes.
+fn collect_bursts(
+    receiver: Receiver<notify::Result<Event>>,
+    watched: &Mutex<Watched>,
+    sender: &Sender<Message>,
+) {
+    let mut burst: Option<Burst> = None;
+    loop {
+        let wait = burst.as_ref().map_or(IDLE_WAIT, |b| {
+            let quiet = QUIET_PERIOD.saturating_sub(b.last.elapsed());
+            quiet.min(MAX_DELAY.saturating_sub(b.start.elapsed()))
+        });
+        match receiver.recv_timeout(wait) {
+            Ok(Ok(event)) if !is_access(&event.kind) => {
+                let watched = watched.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(dir) = &watched.dir else {
+                    continue;
+                };
+                let now = Instant::now();
+                // Events still queued for the previous directory are dropped
+                if burst.as_ref().is_some_and(|b| b.dir != *dir) {
+                    burst = None;
+                }
+                let current = burst.get_or_insert_with(|| Burst {
+                    dir: dir.clone(),
+                    start: now,
+                    last: now,
+                    changes: Changes::default(),
+                });
+                current.last = now;
+                let preview = watched.preview.as_deref();
+                for path in &event.paths {
+                    let parent = path.parent();
+                    if path == dir || parent == Some(dir) {
+                        current.changes.listing = true;
+                    }
+                    if preview.is_some_and(|p| path == p || parent == Some(p)) {
+                        current.changes.preview = true;
+                    }
+                }
+            }
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let over = |b: &mut Burst| {
+            b.last.elapsed() >= QUIET_PERIOD || b.start.elapsed() >= MAX_DELAY
+        };
+        let Some(Burst { dir, changes, .. }) = burst.take_if(over) else {
+            continue;
+        };
+        if changes != Changes::default()
+            && sender.send(Message::FilesChanged { dir, changes }).is_err()
+        {
+            return;
+        }
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Reads (including our own directory listings and previews) change
+/// nothing; only a file closed after writing counts.
+fn is_access(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => false,
+        EventKind::Access(_) => true,
+        EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)) => true,
+        _ => false,
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn test_reports_new_files_after_burst() {
+        let dir = temp_dir("watch").canonicalize().unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = DirWatcher::new(sender).unwrap();
+        watcher.watch(&dir, None);
+        for i in 0..5 {
+            fs::write(dir.join(format!("new{}.txt", i)), b"x").unwrap();
+        }
+
+        match receiver.recv_timeout(Duration::from_secs(5)) {
+            Ok(Message::FilesChanged { dir: changed, changes }) => {
+                assert_eq!(changed, dir);
+                assert!(changes.listing && !changes.preview);
+            }
+            _ => panic!("no change reported"),
+        }
+        // The whole burst is reported once
+        assert!(receiver.recv_timeout(QUIET_PERIOD * 2).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Condvar, Mutex,
+    },
+    thread,
+};
+
+use crate::events::Message;
+
+// =============================================================================
+// Data Types
+// =============================================================================
+
+/// Runs a job per directory on one long-lived thread and posts each result
+/// to the event loop. A request for a directory that is still queued
+/// replaces the queued one, so a burst of refreshes runs the job once.
+pub struct DirWorker {
+    shared: Arc<Shared>,
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    wakeup: Condvar,
+    shutdown: AtomicBool,
+}
+
+#[derive(Default)]
+struct Queue {
+    requests: VecDeque<(PathBuf, u64)>,
+    /// Generation handed to the next request
+    next: u64,
+}
+
+// =============================================================================
+// DirWorker Implementation
+// =============================================================================
+
+impl DirWorker {
+    /// Starts the thread. `job` turns a directory and the generation of its
+    /// request into the message to post.
+    pub fn start<F>(sender: Sender<Message>, job: F) -> DirWorker
+    where
+        F: Fn(PathBuf, u64) -> Message + Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+        let worker = Arc::clone(&shared);
+        thread::spawn(move || loop {
+            let (dir, generation) = {
+                let mut queue = worker.queue.lock().unwrap_or_else(|e| e.into_inner());
+                loop {
+                    if worker.shutdown.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if let Some(request) = queue.requests.pop_front() {
+                        break request;
+                    }
+                    queue = worker.wakeup.wait(queue).unwrap_or_else(|e| e.into_inner());
+                }
+            };
+            if sender.send(job(dir, generation)).is_err() {
+                return;
+            }
+        });
+        DirWorker { shared }
+    }
+
+    /// Queues the job for `dir` and returns the generation of the request.
+    /// A result carrying an older generation for the same directory is stale.
+    pub fn request(&self, dir: PathBuf) -> u64 {
+        let mut queue = self.shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.next += 1;
+        let generation = queue.next;
+        queue.requests.retain(|(queued, _)| *queued != dir);
+        queue.requests.push_back((dir, generation));
+        self.shared.wakeup.notify_one();
+        generation
+    }
+}
+
+impl Drop for DirWorker {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.wakeup.notify_one();
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_requests_for_a_queued_dir_are_merged() {
+        let (sender, receiver) = mpsc::channel();
+        let (start, started) = mpsc::channel();
+        let release = Arc::new(Mutex::new(()));
+        let held = release.lock().unwrap();
+        let gate = Arc::clone(&release);
+        let worker = DirWorker::start(sender, move |dir, generation| {
+            let _ = start.send(());
+            drop(gate.lock());
+            Message::GitStatus {
+                dir,
+                generation,
+                statuses: Default::default(),
+            }
+        });
+
+        // While the first job runs, two requests for "b" collapse into one
+        worker.request(PathBuf::from("a"));
+        started.recv().unwrap();
+        worker.request(PathBuf::from("b"));
+        let latest = worker.request(PathBuf::from("b"));
+        drop(held);
+
+        let results: Vec<(PathBuf, u64)> = receiver
+            .iter()
+            .take(2)
+            .map(|message| match message {
+                Message::GitStatus {
+                    dir, generation, ..
+                } => (dir, generation),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(results, vec![(PathBuf::from("a"), 1), (PathBuf::from("b"), latest)]);
+    }
+}